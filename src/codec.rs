@@ -0,0 +1,74 @@
+//! Base64 and percent-encoding, shared by data: urls and MIME bodies.
+
+/// Standard base64 with padding.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes base64 the way MIME does ([RFC 2045](https://www.rfc-editor.org/rfc/rfc2045#section-6.8)):
+/// characters outside of the alphabet (line breaks, ...) are ignored and the data ends at the padding.
+pub fn base64_decode_lenient(input: &[u8]) -> Vec<u8> {
+    let mut data: Vec<u8> = input
+        .iter()
+        .copied()
+        .take_while(|b| *b != b'=')
+        .filter(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/'))
+        .collect();
+    // a single character can't encode a byte
+    if data.len() % 4 == 1 {
+        data.pop();
+    }
+    data_url::forgiving_base64::decode_to_vec(&data).unwrap_or_default()
+}
+
+/// Encodes the bytes for which `encode` returns true (and all non-ASCII bytes) as `%XX`.
+pub fn percent_encode(bytes: &[u8], encode: impl Fn(u8) -> bool) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for &b in bytes {
+        if !b.is_ascii() || encode(b) {
+            out.push_str(&format!("%{b:02X}"));
+        } else {
+            out.push(b as char);
+        }
+    }
+    out
+}
+
+/// Decodes `%XX` sequences, invalid ones are kept as they are.
+pub fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
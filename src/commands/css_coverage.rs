@@ -0,0 +1,188 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, SyntaxShape, Type, Value};
+
+use crate::HtmlBackend;
+use crate::css::selector::{parse_selector_list, resolve_nesting};
+use crate::css::stylesheet::{Rule, parse_stylesheet};
+
+pub struct CssCoverageCommand;
+
+impl SimplePluginCommand for CssCoverageCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo css coverage"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(Type::String, Type::list(Type::record()))
+            .required(
+                "documents",
+                SyntaxShape::OneOf(vec![
+                    SyntaxShape::String,
+                    SyntaxShape::List(Box::new(SyntaxShape::String)),
+                ]),
+                "html document(s) to match the stylesheet against",
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            count how many elements each selector of each style-rule matches in the given html documents.
+            status is one of "matched", "unmatched", "dynamic" (depends on state like :hover or ::before), "unsupported" (by the backend).
+            use `where unused` to find rules which can be removed.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let input: &String = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let documents: Vec<Value> = match call.req::<Value>(0)? {
+            Value::List { vals, .. } => vals,
+            v => vec![v],
+        };
+
+        #[cfg(feature = "blitz_backend")]
+        let b = crate::BlitzBackend;
+        #[cfg(not(feature = "blitz_backend"))]
+        let b = crate::ScraperBackend;
+
+        let documents = documents
+            .iter()
+            .map(|document| b.parse(document))
+            .collect::<Result<Vec<_>, LabeledError>>()?;
+
+        let stylesheet = parse_stylesheet(input);
+        let mut out: Vec<Value> = Vec::new();
+        collect_coverage(
+            &b,
+            &documents,
+            &stylesheet.rules,
+            &[],
+            &[],
+            call.head,
+            &mut out,
+        );
+        Ok(Value::list(out, call.head))
+    }
+}
+
+fn collect_coverage<B: HtmlBackend>(
+    b: &B,
+    documents: &[B::Document],
+    rules: &[Rule],
+    parent_selectors: &[String],
+    context: &[String],
+    span: Span,
+    out: &mut Vec<Value>,
+) {
+    for rule in rules {
+        match rule {
+            Rule::Style {
+                selectors, rules, ..
+            } => {
+                let selectors: Vec<String> = selectors
+                    .iter()
+                    .map(|s| resolve_nesting(s, parent_selectors))
+                    .collect();
+
+                let mut matched: Vec<&str> = Vec::new();
+                let mut unused = true;
+                let mut selector_reports: Vec<Value> = Vec::new();
+                for selector in selectors.iter() {
+                    let (status, matches) = match_selector(b, documents, selector);
+                    if status != "unmatched" {
+                        unused = false;
+                    }
+                    if status == "matched" {
+                        matched.push(selector);
+                    }
+                    let mut r = Record::new();
+                    r.push("selector", Value::string(selector, span));
+                    r.push(
+                        "matches",
+                        match matches {
+                            Some(v) => Value::int(v as i64, span),
+                            None => Value::nothing(span),
+                        },
+                    );
+                    r.push("status", Value::string(status, span));
+                    selector_reports.push(Value::record(r, span));
+                }
+
+                // an element matched by several selectors of the rule is counted once
+                let total = if matched.is_empty() {
+                    0
+                } else {
+                    match_selector(b, documents, &matched.join(", "))
+                        .1
+                        .unwrap_or(0)
+                };
+
+                let mut r = Record::new();
+                r.push(
+                    "context",
+                    Value::list(
+                        context.iter().map(|c| Value::string(c, span)).collect(),
+                        span,
+                    ),
+                );
+                r.push("selectors", Value::list(selector_reports, span));
+                r.push("matches", Value::int(total as i64, span));
+                r.push("unused", Value::bool(unused, span));
+                out.push(Value::record(r, span));
+
+                collect_coverage(b, documents, rules, &selectors, context, span, out);
+            }
+            Rule::At {
+                name,
+                prelude,
+                block: Some((_, rules)),
+            } => {
+                // keyframe selectors (`from`, `50%`) are not element selectors
+                if name.ends_with("keyframes") {
+                    continue;
+                }
+                let mut context = context.to_vec();
+                context.push(format!("@{name} {prelude}").trim_end().to_owned());
+                collect_coverage(b, documents, rules, parent_selectors, &context, span, out);
+            }
+            Rule::At { block: None, .. } => {}
+        }
+    }
+}
+
+/// Returns `(status, match_count)`
+fn match_selector<B: HtmlBackend>(
+    b: &B,
+    documents: &[B::Document],
+    selector: &str,
+) -> (&'static str, Option<usize>) {
+    if let Ok(info) = parse_selector_list(selector)
+        && info.iter().any(|i| i.dynamic)
+    {
+        return ("dynamic", None);
+    }
+    let mut count: usize = 0;
+    for document in documents {
+        match b.css_query(document, selector) {
+            Ok(nodes) => count += nodes.len(),
+            Err(_) => return ("unsupported", None),
+        }
+    }
+    (
+        if count == 0 { "unmatched" } else { "matched" },
+        Some(count),
+    )
+}
@@ -0,0 +1,89 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Type, Value};
+
+use crate::css::properties::parse_expanded_declarations;
+use crate::css::stylesheet::{Declaration, parse_declaration_list};
+
+pub struct CssDeclarationsCommand;
+
+impl SimplePluginCommand for CssDeclarationsCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo css declarations"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(Type::String, Type::record())
+            .switch(
+                "expand",
+                "expand shorthands into longhands (drops invalid declarations)",
+                Some('e'),
+            )
+            .switch(
+                "with-importance",
+                "return {value, important} records instead of strings",
+                Some('i'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            parse a declaration block (such as a style="" attribute) into a record<property: value>.
+            if a property is declared multiple times the one which wins the cascade is returned.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let input: &String = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let with_importance = call.has_flag("with-importance")?;
+        let span = call.head;
+
+        let declarations: Vec<Declaration> = if call.has_flag("expand")? {
+            parse_expanded_declarations(input)
+        } else {
+            let mut winners: Vec<Declaration> = Vec::new();
+            for declaration in parse_declaration_list(input).0 {
+                if let Some(i) = winners
+                    .iter()
+                    .position(|d| d.property == declaration.property)
+                {
+                    if winners[i].important && !declaration.important {
+                        continue;
+                    }
+                    winners.remove(i);
+                }
+                winners.push(declaration);
+            }
+            winners
+        };
+
+        let mut out = Record::new();
+        for declaration in declarations {
+            out.push(
+                declaration.property,
+                if with_importance {
+                    let mut r = Record::new();
+                    r.push("value", Value::string(declaration.value, span));
+                    r.push("important", Value::bool(declaration.important, span));
+                    Value::record(r, span)
+                } else {
+                    Value::string(declaration.value, span)
+                },
+            );
+        }
+        Ok(Value::record(out, span))
+    }
+}
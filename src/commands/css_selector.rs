@@ -0,0 +1,69 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Type, Value};
+
+use crate::css::selector::parse_selector_list;
+
+pub struct CssSelectorCommand;
+
+impl SimplePluginCommand for CssSelectorCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo css selector"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name()).input_output_type(Type::String, Type::list(Type::record()))
+    }
+
+    fn description(&self) -> &str {
+        "parse a css selector list (components, specificity as [a b c], backend support)"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let input_span = input.span();
+        let input: &String = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let span = call.head;
+        let selectors = parse_selector_list(input).map_err(|err| {
+            LabeledError::new(format!(
+                "Failed to parse CSS selector at line {} column {}: {}",
+                err.line, err.column, err.message
+            ))
+            .with_label(&err.message, err.span_in(input, input_span))
+        })?;
+
+        Ok(Value::list(
+            selectors
+                .iter()
+                .map(|selector| {
+                    let mut out = selector.to_nu(span);
+                    let mut backends = Record::new();
+                    #[cfg(feature = "scraper_backend")]
+                    backends.push(
+                        "scraper",
+                        Value::bool(scraper::Selector::parse(&selector.css).is_ok(), span),
+                    );
+                    // blitz uses the same (stylo) selector parser
+                    #[cfg(feature = "blitz_backend")]
+                    backends.push("blitz", Value::bool(true, span));
+                    if let Value::Record { val, .. } = &mut out {
+                        val.to_mut().push("backends", Value::record(backends, span));
+                    }
+                    out
+                })
+                .collect(),
+            span,
+        ))
+    }
+}
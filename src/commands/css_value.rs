@@ -0,0 +1,83 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Spanned, SyntaxShape, Type, Value};
+
+use crate::css::properties::{PropertyValueError, parse_property_value};
+use crate::css::values::value2nu;
+
+pub struct CssValueCommand;
+
+impl SimplePluginCommand for CssValueCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo css value parse"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(Type::String, Type::Any)
+            .required(
+                "property",
+                SyntaxShape::String,
+                "the property the value belongs to",
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            parse the value of a css property into typed values.
+            colors become {r, g, b, alpha}, lengths/angles/percentages {value, unit}, multiple values a list.
+            for shorthands a record<longhand: value> is returned.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let input_span = input.span();
+        let input: &String = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let property: Spanned<String> = call.req(0)?;
+        let span = call.head;
+
+        let declarations = match parse_property_value(&property.item, input) {
+            Ok(declarations) => declarations,
+            Err(PropertyValueError::UnknownProperty) => {
+                return Err(LabeledError::new("Unknown CSS property").with_label(
+                    format!("'{}' is not supported", property.item),
+                    property.span,
+                ));
+            }
+            Err(PropertyValueError::InvalidValue) => {
+                return Err(LabeledError::new("Invalid CSS value").with_label(
+                    format!("not a valid value for '{}'", property.item),
+                    input_span,
+                ));
+            }
+        };
+
+        match declarations.as_slice() {
+            [declaration] if declaration.property.eq_ignore_ascii_case(&property.item) => {
+                Ok(value2nu(&declaration.property, &declaration.value, span))
+            }
+            _ => {
+                let mut out = Record::new();
+                for declaration in declarations.iter() {
+                    out.push(
+                        &declaration.property,
+                        value2nu(&declaration.property, &declaration.value, span),
+                    );
+                }
+                Ok(Value::record(out, span))
+            }
+        }
+    }
+}
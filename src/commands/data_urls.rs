@@ -0,0 +1,106 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, SyntaxShape, Type, Value};
+use scraper::ElementRef;
+
+use super::parse_data_url::parse_data_url;
+use crate::css::urls::find_urls;
+use crate::srcset::parse_srcset;
+use crate::{HtmlBackend, NuDataFormat, format_qual_name};
+
+pub struct DataUrlsCommand;
+
+impl SimplePluginCommand for DataUrlsCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html data-urls"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::table()),
+                (Type::Binary, Type::table()),
+            ])
+            .named("format", SyntaxShape::String, "format of the element", None)
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            find the data: urls embedded in a document: in attributes (src, href, srcset, ...),
+            url()s in style="" attributes and in <style> elements.
+            each row contains the element, the attribute (null for <style>), the css property (null outside of css)
+            and the decoded url in the format of 'servo data-url parse' (mime, body, text, base64, fragment, fragment_decoded).
+            invalid data: urls are skipped.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let format = NuDataFormat::parse(call.get_flag_value("format"), NuDataFormat::Html)?;
+        let b = crate::ScraperBackend;
+        let html = b.parse_with_doctype(input)?;
+        let span = call.head;
+
+        let mut rows = Vec::new();
+        for element in html.tree.nodes().filter_map(ElementRef::wrap) {
+            // (attribute, property, url)
+            let mut found: Vec<(Option<String>, Option<String>, String)> = Vec::new();
+            for (name, value) in element.value().attrs.iter() {
+                let attribute = format_qual_name(name);
+                match &*name.local {
+                    "style" => find_urls(value, &mut |url, property| {
+                        found.push((
+                            Some(attribute.clone()),
+                            property.map(str::to_owned),
+                            url.to_owned(),
+                        ))
+                    }),
+                    "srcset" | "imagesrcset" => {
+                        for candidate in parse_srcset(value) {
+                            found.push((Some(attribute.clone()), None, candidate.url));
+                        }
+                    }
+                    _ => found.push((Some(attribute), None, value.to_string())),
+                }
+            }
+            if &*element.value().name.local == "style" {
+                let css: String = element.text().collect();
+                find_urls(&css, &mut |url, property| {
+                    found.push((None, property.map(str::to_owned), url.to_owned()))
+                });
+            }
+
+            for (attribute, property, url) in found {
+                if !is_data_url(&url) {
+                    continue;
+                }
+                let Ok(data) = parse_data_url(url.trim(), span, span) else {
+                    continue;
+                };
+                let optional =
+                    |s: Option<String>| s.map_or(Value::nothing(span), |s| Value::string(s, span));
+                let mut row = Record::new();
+                row.push("element", b.node2nu(&html, element, format, span)?);
+                row.push("attribute", optional(attribute));
+                row.push("property", optional(property));
+                for (key, value) in data {
+                    row.push(key, value);
+                }
+                rows.push(Value::record(row, span));
+            }
+        }
+        Ok(Value::list(rows, span))
+    }
+}
+
+fn is_data_url(url: &str) -> bool {
+    url.trim_start()
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+}
@@ -0,0 +1,101 @@
+use encoding_rs::Encoding;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, SyntaxShape, Type, Value};
+
+pub struct DecodeCommand;
+
+impl SimplePluginCommand for DecodeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo decode"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(
+                Type::Binary,
+                Type::Record(Box::new([
+                    (String::from("text"), Type::String),
+                    (String::from("encoding"), Type::String),
+                    (String::from("replaced"), Type::Bool),
+                ])),
+            )
+            .optional(
+                "encoding",
+                SyntaxShape::String,
+                "charset label (latin1, shift_jis, ...) or mime type with a charset parameter (default: utf-8)",
+            )
+            .switch(
+                "no-bom",
+                "don't let a byte order mark override the encoding (it is still removed if it matches)",
+                Some('n'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            decode binary into text like browsers do (WHATWG Encoding Standard: its labels and decoders).
+            returns the text, the name of the encoding that was used (a byte order mark wins over the label)
+            and whether invalid bytes were replaced with U+FFFD.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let Value::Binary { val: bytes, .. } = input else {
+            return Err(LabeledError::new("Invalid input (expected binary)")
+                .with_label("here", input.span()));
+        };
+        let encoding = match call.opt::<Value>(0)? {
+            Some(label) => encoding_from_value(&label)?,
+            None => encoding_rs::UTF_8,
+        };
+
+        let (text, used, replaced) = if call.has_flag("no-bom")? {
+            let (text, replaced) = encoding.decode_with_bom_removal(bytes);
+            (text, encoding, replaced)
+        } else {
+            encoding.decode(bytes)
+        };
+
+        let mut record = Record::new();
+        record.push("text", Value::string(text, span));
+        record.push("encoding", Value::string(used.name(), span));
+        record.push("replaced", Value::bool(replaced, span));
+        Ok(Value::record(record, span))
+    }
+}
+
+/// The encoding of a label (`latin1`) or of the charset parameter of a mime type (`text/html; charset=latin1`).
+pub(crate) fn encoding_from_value(value: &Value) -> Result<&'static Encoding, LabeledError> {
+    let input = value.as_str()?;
+    let label = if input.contains('/') {
+        charset_parameter(input).ok_or_else(|| {
+            LabeledError::new("Missing charset")
+                .with_label("the mime type has no charset parameter", value.span())
+        })?
+    } else {
+        input.trim().to_owned()
+    };
+    Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+        LabeledError::new(format!("Unknown encoding '{label}'"))
+            .with_label("not a label of the Encoding Standard", value.span())
+            .with_help("see https://encoding.spec.whatwg.org/#names-and-labels")
+    })
+}
+
+fn charset_parameter(mime: &str) -> Option<String> {
+    mime.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_owned())
+    })
+}
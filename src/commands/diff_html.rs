@@ -0,0 +1,105 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, SyntaxShape, Type, Value};
+
+use crate::diff::{Change, DiffOptions, diff};
+use crate::serialize::Syntax;
+
+pub struct DiffHtmlCommand;
+
+impl SimplePluginCommand for DiffHtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html diff"
+    }
+
+    fn signature(&self) -> Signature {
+        diff_signature(self.name(), "html").switch(
+            "fragment",
+            "the documents are html fragments (don't add <html>, <head> and <body>)",
+            Some('f'),
+        )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            compare the input (old) with another html document (new) and return the changes:
+            inserted, removed, moved, attribute-added, attribute-changed, attribute-removed, text-changed and comment-changed.
+            path is the css path of the changed element (of the parent element for text and comments),
+            in the new document except for removed nodes. for moved nodes old and new are the old and new paths.
+            attributes are compared by name, their order never matters (it isn't kept by the parser).
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let options = diff_options(call, Syntax::Html)?;
+        let other: Value = call.req(0)?;
+        let b = crate::ScraperBackend;
+        let changes = if call.has_flag("fragment")? {
+            let old = b.parse_fragment(input)?;
+            let new = b.parse_fragment(&other)?;
+            diff(*old.root_element(), *new.root_element(), &options)
+        } else {
+            let old = b.parse_with_doctype(input)?;
+            let new = b.parse_with_doctype(&other)?;
+            diff(old.tree.root(), new.tree.root(), &options)
+        };
+        Ok(changes_to_value(changes, call.head))
+    }
+}
+
+/// The signature shared with `servo xml diff`.
+pub(crate) fn diff_signature(name: &str, syntax: &str) -> Signature {
+    Signature::new(name)
+        .input_output_types(vec![
+            (Type::String, Type::table()),
+            (Type::Binary, Type::table()),
+        ])
+        .required(
+            "other",
+            SyntaxShape::OneOf(vec![SyntaxShape::Binary, SyntaxShape::String]),
+            format!("the new {syntax} document"),
+        )
+        .switch(
+            "ignore-whitespace",
+            "ignore text nodes which only contain whitespace",
+            Some('w'),
+        )
+        .switch("ignore-comments", "ignore comments", Some('c'))
+}
+
+pub(crate) fn diff_options(
+    call: &nu_plugin::EvaluatedCall,
+    syntax: Syntax,
+) -> Result<DiffOptions, LabeledError> {
+    Ok(DiffOptions {
+        syntax,
+        ignore_whitespace: call.has_flag("ignore-whitespace")?,
+        ignore_comments: call.has_flag("ignore-comments")?,
+    })
+}
+
+pub(crate) fn changes_to_value(changes: Vec<Change>, span: Span) -> Value {
+    let optional = |s: Option<String>| s.map_or(Value::nothing(span), |s| Value::string(s, span));
+    Value::list(
+        changes
+            .into_iter()
+            .map(|change| {
+                let mut record = Record::new();
+                record.push("change", Value::string(change.kind.as_str(), span));
+                record.push("path", Value::string(change.path, span));
+                record.push("attribute", optional(change.attribute));
+                record.push("old", optional(change.old));
+                record.push("new", optional(change.new));
+                Value::record(record, span)
+            })
+            .collect(),
+        span,
+    )
+}
@@ -0,0 +1,45 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Value};
+
+use super::diff_html::{changes_to_value, diff_options, diff_signature};
+use crate::diff::diff;
+use crate::serialize::Syntax;
+
+pub struct DiffXmlCommand;
+
+impl SimplePluginCommand for DiffXmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo xml diff"
+    }
+
+    fn signature(&self) -> Signature {
+        diff_signature(self.name(), "xml")
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            compare the input (old) with another xml document (new) and return the changes,
+            see 'servo html diff' for the columns.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let options = diff_options(call, Syntax::Xml)?;
+        let other: Value = call.req(0)?;
+        let b = crate::ScraperBackend;
+        let old = b.parse_xml(input)?;
+        let new = b.parse_xml(&other)?;
+        Ok(changes_to_value(
+            diff(old.tree.root(), new.tree.root(), &options),
+            call.head,
+        ))
+    }
+}
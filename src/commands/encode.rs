@@ -0,0 +1,72 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, SyntaxShape, Type, Value};
+
+use super::decode::encoding_from_value;
+
+pub struct EncodeCommand;
+
+impl SimplePluginCommand for EncodeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo encode"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(Type::String, Type::Binary)
+            .optional(
+                "encoding",
+                SyntaxShape::String,
+                "charset label (latin1, shift_jis, ...) or mime type with a charset parameter (default: utf-8)",
+            )
+            .switch(
+                "strict",
+                "fail on characters the encoding can't represent (instead of writing &#...; escapes)",
+                Some('s'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            encode text like browsers do for forms and urls (WHATWG Encoding Standard).
+            characters the encoding can't represent become html numeric escapes (&#8364;).
+            utf-16 and replacement can't be used for encoding, they produce utf-8.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let Value::String { val: text, .. } = input else {
+            return Err(LabeledError::new("Invalid input (expected string)")
+                .with_label("here", input.span()));
+        };
+        let encoding = match call.opt::<Value>(0)? {
+            Some(label) => encoding_from_value(&label)?,
+            None => encoding_rs::UTF_8,
+        };
+
+        let (bytes, used, unmappable) = encoding.encode(text);
+        if unmappable && call.has_flag("strict")? {
+            let c = text
+                .chars()
+                .find(|c| {
+                    let mut buffer = [0; 4];
+                    used.encode(c.encode_utf8(&mut buffer)).2
+                })
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            return Err(LabeledError::new(format!(
+                "'{c}' (U+{:04X}) can't be encoded in {}",
+                c as u32,
+                used.name()
+            ))
+            .with_label("here", input.span()));
+        }
+        Ok(Value::binary(bytes.into_owned(), call.head))
+    }
+}
@@ -0,0 +1,47 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Type, Value};
+
+use super::mime::MediaType;
+use crate::mime_types::extensions;
+
+pub struct ExtensionsMimeCommand;
+
+impl SimplePluginCommand for ExtensionsMimeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime extensions"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name()).input_output_types(vec![
+            (Type::String, Type::List(Box::new(Type::String))),
+            (Type::record(), Type::List(Box::new(Type::String))),
+        ])
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            the common file extensions (without dot) of a mime type (string or record in the format of 'servo mime parse'),
+            the usual one first. parameters are ignored, unknown types have no extensions.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let mime = MediaType::from_value(input)?;
+        Ok(Value::list(
+            extensions(&mime.essence())
+                .iter()
+                .map(|e| Value::string(*e, span))
+                .collect(),
+            span,
+        ))
+    }
+}
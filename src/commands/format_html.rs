@@ -0,0 +1,92 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, SyntaxShape, Type, Value};
+
+use crate::serialize::{FormatOptions, Syntax, format_children};
+
+pub struct FormatHtmlCommand;
+
+impl SimplePluginCommand for FormatHtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html format"
+    }
+
+    fn signature(&self) -> Signature {
+        format_signature(self.name()).switch(
+            "fragment",
+            "the input is an html fragment (don't add <html>, <head> and <body>)",
+            Some('f'),
+        )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            pretty-print html: one block-level element per line, indented by depth.
+            text and inline elements (<b>, <a>, ...) stay on one line, <pre>, <textarea>, <script> and <style> are kept as they are.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let options = format_options(call, Syntax::Html)?;
+        let b = crate::ScraperBackend;
+        let out = if call.has_flag("fragment")? {
+            let html = b.parse_fragment(input)?;
+            format_children(*html.root_element(), &options)
+        } else {
+            let html = b.parse_with_doctype(input)?;
+            format_children(html.tree.root(), &options)
+        };
+        Ok(Value::string(out, call.head))
+    }
+}
+
+/// The signature shared with `servo xml format`.
+pub(crate) fn format_signature(name: &str) -> Signature {
+    Signature::new(name)
+        .input_output_types(vec![
+            (Type::String, Type::String),
+            (Type::Binary, Type::String),
+        ])
+        .named(
+            "indent",
+            SyntaxShape::Int,
+            "spaces per indentation level (default: 2)",
+            Some('i'),
+        )
+        .named(
+            "width",
+            SyntaxShape::Int,
+            "start tags longer than this get one attribute per line (default: 80)",
+            Some('w'),
+        )
+        .switch("sort-attributes", "sort attributes by name", Some('s'))
+}
+
+pub(crate) fn format_options(
+    call: &nu_plugin::EvaluatedCall,
+    syntax: Syntax,
+) -> Result<FormatOptions, LabeledError> {
+    let positive = |name: &str, default: usize| -> Result<usize, LabeledError> {
+        match call.get_flag::<i64>(name)? {
+            Some(v) if v < 0 => Err(
+                LabeledError::new(format!("'--{name}' must not be negative"))
+                    .with_label("negative", call.head),
+            ),
+            Some(v) => Ok(v as usize),
+            None => Ok(default),
+        }
+    };
+    Ok(FormatOptions {
+        syntax,
+        indent: positive("indent", 2)?,
+        width: positive("width", 80)?,
+        sort_attributes: call.has_flag("sort-attributes")?,
+    })
+}
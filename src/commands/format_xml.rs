@@ -0,0 +1,41 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Value};
+
+use super::format_html::{format_options, format_signature};
+use crate::serialize::{Syntax, format_children};
+
+pub struct FormatXmlCommand;
+
+impl SimplePluginCommand for FormatXmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo xml format"
+    }
+
+    fn signature(&self) -> Signature {
+        format_signature(self.name())
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            pretty-print xml: one element per line, indented by depth.
+            elements with mixed content (text and elements) stay on one line, xml:space="preserve" is respected.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let options = format_options(call, Syntax::Xml)?;
+        let xml = crate::ScraperBackend.parse_xml(input)?;
+        Ok(Value::string(
+            format_children(xml.tree.root(), &options),
+            call.head,
+        ))
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Type, Value};
+
+use super::mime::mime_to_record;
+use crate::mime_types::mime_types_from_extension;
+use crate::sniff::{RESOURCE_HEADER_LENGTH, sniff_unknown};
+
+pub struct FromPathMimeCommand;
+
+impl SimplePluginCommand for FromPathMimeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime from-path"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            // a record, a list of records with --all
+            .input_output_type(Type::String, Type::Any)
+            .switch(
+                "all",
+                "return all mime types using the extension, most likely first",
+                Some('a'),
+            )
+            .switch(
+                "sniff",
+                "read the file and sniff its content if the extension is unknown (or .bin)",
+                Some('s'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            guess the mime type of a file from its extension (using a compiled-in table of common types).
+            unknown extensions are application/octet-stream, unless --sniff can read the file.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let path = match input {
+            Value::String { val, .. } => Path::new(val),
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)")
+                    .with_label("here", input.span()));
+            }
+        };
+
+        let mut types = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(mime_types_from_extension)
+            .unwrap_or_default();
+        let unknown = types.iter().all(|t| *t == "application/octet-stream");
+        if unknown && call.has_flag("sniff")? {
+            let path = if path.is_absolute() {
+                path.to_owned()
+            } else {
+                Path::new(&engine.get_current_dir()?).join(path)
+            };
+            let mut bytes = Vec::new();
+            let read = File::open(path).and_then(|file| {
+                file.take(RESOURCE_HEADER_LENGTH as u64)
+                    .read_to_end(&mut bytes)
+            });
+            if read.is_ok() {
+                types = vec![sniff_unknown(&bytes, true)];
+            }
+        }
+        if types.is_empty() {
+            types.push("application/octet-stream");
+        }
+
+        let record = |essence: &str| {
+            let mime =
+                mime::Mime::from_str(essence).expect("the mime types of the table are valid");
+            mime_to_record(&mime, span)
+        };
+        if call.has_flag("all")? {
+            Ok(Value::list(types.into_iter().map(record).collect(), span))
+        } else {
+            Ok(record(types[0]))
+        }
+    }
+}
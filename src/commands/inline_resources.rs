@@ -0,0 +1,396 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use data_url::mime::Mime;
+use ego_tree::NodeId;
+use html5ever::{QualName, local_name, ns};
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Span, Spanned, SyntaxShape, Type, Value};
+use scraper::node::{Element, Text};
+use scraper::{Html, Node};
+
+use super::sanitize_html::url_scheme;
+use super::to_data_url::encode_data_url;
+use crate::codec::percent_decode;
+use crate::css::urls::rewrite_urls;
+use crate::srcset::{parse_srcset, serialize_srcset};
+use crate::{HtmlBackend, HtmlBackendMut};
+
+pub struct InlineResourcesCommand;
+
+impl SimplePluginCommand for InlineResourcesCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html inline-resources"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::String),
+                (Type::Binary, Type::String),
+            ])
+            .named(
+                "root",
+                SyntaxShape::Directory,
+                "directory containing the document (relative urls are resolved against it, default: current directory)",
+                Some('r'),
+            )
+            .switch(
+                "ignore-missing",
+                "keep references to missing files instead of failing",
+                Some('i'),
+            )
+            .switch(
+                "fragment",
+                "the input is an html fragment (don't add <html>, <head> and <body>)",
+                Some('f'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            embed local files into the document to make it self-contained:
+            <link rel=stylesheet> becomes <style>, <script src> gets the script as content,
+            images (src, srcset, poster), icons and url()s in css become data: urls (the mime type is sniffed).
+            urls of stylesheets are resolved relative to the stylesheet, @imports are embedded too.
+            absolute paths (/img/a.png) are resolved against --root, files outside of it are not read.
+            remote urls (https://..., //cdn...) are kept.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let root = match call.get_flag::<Spanned<String>>("root")? {
+            Some(root) if Path::new(&root.item).is_absolute() => root,
+            Some(root) => Spanned {
+                item: Path::new(&engine.get_current_dir()?)
+                    .join(&root.item)
+                    .to_string_lossy()
+                    .into_owned(),
+                span: root.span,
+            },
+            None => Spanned {
+                item: engine.get_current_dir()?,
+                span: call.head,
+            },
+        };
+        let root = Path::new(&root.item).canonicalize().map_err(|err| {
+            LabeledError::new("Invalid root directory").with_label(err.to_string(), root.span)
+        })?;
+        let inliner = Inliner {
+            root,
+            ignore_missing: call.has_flag("ignore-missing")?,
+            span: call.head,
+        };
+
+        let b = crate::ScraperBackend;
+        let fragment = call.has_flag("fragment")?;
+        let mut html = if fragment {
+            b.parse_fragment(input)?
+        } else {
+            b.parse_with_doctype(input)?
+        };
+
+        let elements: Vec<NodeId> = html
+            .tree
+            .nodes()
+            .filter(|n| n.value().is_element())
+            .map(|n| n.id())
+            .collect();
+        for element in elements {
+            inliner.inline_element(&mut html, element)?;
+        }
+
+        let out = if fragment {
+            b.inner_html(&html, b.get_root_node(&html)?)?
+        } else {
+            b.document_html(&html)?
+        };
+        Ok(Value::string(out, call.head))
+    }
+}
+
+struct LocalFile {
+    path: PathBuf,
+    bytes: Vec<u8>,
+    fragment: Option<String>,
+}
+
+struct Inliner {
+    root: PathBuf,
+    ignore_missing: bool,
+    span: Span,
+}
+
+impl Inliner {
+    fn inline_element(&self, html: &mut Html, id: NodeId) -> Result<(), LabeledError> {
+        let b = crate::ScraperBackend;
+        let Some(Node::Element(element)) = html.tree.get(id).map(|n| n.value()) else {
+            return Ok(());
+        };
+        if element.name.ns != ns!(html) {
+            return Ok(());
+        }
+        let tag = element.name.local.clone();
+        let rel: Vec<String> = element
+            .attr("rel")
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+            .map(|r| r.to_ascii_lowercase())
+            .collect();
+        let root = self.root.clone();
+
+        if let Some(style) = element.attr("style") {
+            let style = self.rewrite_css(style, &root, &[])?;
+            b.set_attribute(html, id, "style", &style)?;
+        }
+        let mut url_attributes: Vec<&str> = match &*tag {
+            "img" | "source" | "audio" | "track" | "embed" => vec!["src"],
+            "video" => vec!["src", "poster"],
+            "input" => vec!["src"],
+            "link" if rel.iter().any(|r| r.contains("icon")) => vec!["href"],
+            _ => vec![],
+        };
+        if matches!(&*tag, "img" | "source") {
+            url_attributes.push("srcset");
+        }
+        for attribute in url_attributes {
+            let Some(value) = b.get_attribute(html, id, attribute) else {
+                continue;
+            };
+            let new = if attribute == "srcset" {
+                let mut candidates = parse_srcset(&value);
+                for candidate in candidates.iter_mut() {
+                    if let Some(url) = self.data_url(&candidate.url, &root, &[])? {
+                        candidate.url = url;
+                    }
+                }
+                Some(serialize_srcset(&candidates))
+            } else {
+                self.data_url(&value, &root, &[])?
+            };
+            if let Some(new) = new {
+                b.set_attribute(html, id, attribute, &new)?;
+            }
+        }
+
+        match &*tag {
+            "style" => {
+                let css: String = html
+                    .tree
+                    .get(id)
+                    .into_iter()
+                    .flat_map(|n| n.children())
+                    .filter_map(|c| c.value().as_text().map(|t| t.to_string()))
+                    .collect();
+                let css = self.rewrite_css(&css, &root, &[])?;
+                b.set_text(html, id, &escape_end_tag(&css, "style"))?;
+            }
+            // alternate stylesheets are disabled until the user picks them
+            "link"
+                if rel.iter().any(|r| r == "stylesheet")
+                    && !rel.iter().any(|r| r == "alternate") =>
+            {
+                let Some(href) = b.get_attribute(html, id, "href") else {
+                    return Ok(());
+                };
+                let Some(file) = self.read(&href, &root)? else {
+                    return Ok(());
+                };
+                let css = self.rewrite_css(
+                    &decode_utf8(&file.bytes),
+                    parent(&file.path),
+                    std::slice::from_ref(&file.path),
+                )?;
+                let media = b.get_attribute(html, id, "media");
+                replace_with_style(html, id, &escape_end_tag(&css, "style"), media);
+            }
+            "script" => {
+                let Some(src) = b.get_attribute(html, id, "src") else {
+                    return Ok(());
+                };
+                let Some(file) = self.read(&src, &root)? else {
+                    return Ok(());
+                };
+                for attribute in ["src", "integrity", "crossorigin", "async", "defer"] {
+                    b.remove_attribute(html, id, attribute)?;
+                }
+                b.set_text(
+                    html,
+                    id,
+                    &escape_end_tag(&decode_utf8(&file.bytes), "script"),
+                )?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Embeds the files referenced by `url()`s and `@import`s of a stylesheet (relative to `base`).
+    /// `ancestors` are the (canonical) paths of the stylesheets containing it.
+    fn rewrite_css(
+        &self,
+        css: &str,
+        base: &Path,
+        ancestors: &[PathBuf],
+    ) -> Result<String, LabeledError> {
+        let mut error = None;
+        let css = rewrite_urls(css, &mut |url| match self.data_url(url, base, ancestors) {
+            Ok(data_url) => data_url,
+            Err(err) => {
+                error.get_or_insert(err);
+                None
+            }
+        });
+        match error {
+            Some(err) => Err(err),
+            None => Ok(css),
+        }
+    }
+
+    /// The `data:` url of a local file (`None` for remote urls, ignored missing files
+    /// and stylesheets importing their ancestors, which would be a cycle).
+    fn data_url(
+        &self,
+        url: &str,
+        base: &Path,
+        ancestors: &[PathBuf],
+    ) -> Result<Option<String>, LabeledError> {
+        let Some(LocalFile {
+            path,
+            bytes,
+            fragment,
+        }) = self.read(url, base)?
+        else {
+            return Ok(None);
+        };
+        let mime = crate::sniff::sniff_image(&bytes)
+            .or_else(|| crate::sniff::sniff_font(&bytes))
+            .or_else(|| crate::mime_types::mime_type_from_path(&path))
+            .unwrap_or("application/octet-stream");
+        let mut data_url = if mime == "text/css" {
+            if ancestors.contains(&path) {
+                return Ok(None);
+            }
+            let css = self.rewrite_css(
+                &decode_utf8(&bytes),
+                parent(&path),
+                &[ancestors, std::slice::from_ref(&path)].concat(),
+            )?;
+            encode_data_url(&parse_mime(mime), css.as_bytes(), None)
+        } else {
+            encode_data_url(&parse_mime(mime), &bytes, None)
+        };
+        if let Some(fragment) = fragment {
+            data_url.push('#');
+            data_url.push_str(&fragment);
+        }
+        Ok(Some(data_url))
+    }
+
+    /// Reads the file referenced by a (local) url.
+    fn read(&self, url: &str, base: &Path) -> Result<Option<LocalFile>, LabeledError> {
+        let url = url.trim();
+        if url.is_empty()
+            || url.starts_with('#')
+            || url.starts_with("//")
+            || url_scheme(url).is_some()
+        {
+            return Ok(None);
+        }
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment.to_owned())),
+            None => (url, None),
+        };
+        let url = url.split_once('?').map_or(url, |(url, _)| url);
+        let path = String::from_utf8_lossy(&percent_decode(url)).into_owned();
+        let path = match path.strip_prefix('/') {
+            Some(absolute) => self.root.join(absolute),
+            None => base.join(path),
+        };
+
+        let missing = |reason: String| {
+            if self.ignore_missing {
+                Ok(None)
+            } else {
+                Err(LabeledError::new(format!("Failed to inline '{url}'"))
+                    .with_label(reason, self.span)
+                    .with_help("use --ignore-missing to keep references to missing files"))
+            }
+        };
+        let path = match path.canonicalize() {
+            Ok(path) => path,
+            Err(err) => return missing(format!("{}: {err}", path.display())),
+        };
+        if !path.starts_with(&self.root) {
+            return missing(format!(
+                "{} is outside of the root directory",
+                path.display()
+            ));
+        }
+        match std::fs::read(&path) {
+            Ok(bytes) => Ok(Some(LocalFile {
+                path,
+                bytes,
+                fragment,
+            })),
+            Err(err) => missing(format!("{}: {err}", path.display())),
+        }
+    }
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent().unwrap_or(path)
+}
+
+fn parse_mime(mime: &str) -> Mime {
+    Mime::from_str(mime).expect("the mime types of the table are valid")
+}
+
+fn decode_utf8(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Prevents `</style` in a stylesheet (or `</script` in a script) from ending the element.
+fn escape_end_tag(text: &str, tag: &str) -> String {
+    let needle = format!("</{tag}");
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.to_ascii_lowercase().find(&needle) {
+        out.push_str(&rest[..i]);
+        out.push_str("<\\/");
+        out.push_str(&rest[i + 2..i + needle.len()]);
+        rest = &rest[i + needle.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replaces a `<link rel=stylesheet>` with a `<style>` element.
+fn replace_with_style(html: &mut Html, link: NodeId, css: &str, media: Option<String>) {
+    let attributes = media
+        .into_iter()
+        .map(|media| html5ever::Attribute {
+            name: QualName::new(None, ns!(), local_name!("media")),
+            value: media.into(),
+        })
+        .collect();
+    let style = Element::new(
+        QualName::new(None, ns!(html), local_name!("style")),
+        attributes,
+    );
+    let Some(mut node) = html.tree.get_mut(link) else {
+        return;
+    };
+    node.insert_before(Node::Element(style))
+        .append(Node::Text(Text { text: css.into() }));
+    node.detach();
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use html5ever::{QualName, local_name, ns};
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Type, Value};
+use scraper::{Html, Selector};
+
+use crate::HtmlBackendMut;
+use crate::css::selector::{parse_selector_list, resolve_nesting};
+use crate::css::stylesheet::{Declaration, Rule, parse_declaration_list, parse_stylesheet};
+
+pub struct InlineStylesCommand;
+
+impl SimplePluginCommand for InlineStylesCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html inline-styles"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name()).input_output_types(vec![
+            (Type::String, Type::String),
+            (Type::Binary, Type::String),
+        ])
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            move the rules of <style> elements into style="" attributes (for html emails).
+            rules which can't be inlined (@media, :hover, ::before, ...) are kept in a <style> element.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let mut html = crate::ScraperBackend.parse_with_doctype(input)?;
+
+        let mut style_elements: Vec<NodeId> = Vec::new();
+        let mut stylesheets: Vec<String> = Vec::new();
+        for element in html.select(&Selector::parse("style").unwrap()) {
+            // stylesheets for other media (print, ...) can't be inlined
+            if let Some(media) = element.attr("media")
+                && !matches!(media.trim(), "" | "all" | "screen")
+            {
+                continue;
+            }
+            style_elements.push(element.id());
+            stylesheets.push(element.text().collect());
+        }
+
+        let mut inliner = Inliner {
+            html: &html,
+            order: 0,
+            matched: HashMap::new(),
+            kept: Vec::new(),
+        };
+        for stylesheet in stylesheets.iter() {
+            for rule in parse_stylesheet(stylesheet).rules.iter() {
+                inliner.add_rule(rule, &[]);
+            }
+        }
+        let Inliner { matched, kept, .. } = inliner;
+
+        let mut updates: Vec<(NodeId, String)> = Vec::new();
+        for (node_id, mut declarations) in matched.into_iter() {
+            let element = scraper::ElementRef::wrap(html.tree.get(node_id).unwrap()).unwrap();
+            if let Some(style) = element.attr("style") {
+                for declaration in parse_declaration_list(style).0 {
+                    declarations.push((
+                        (declaration.important, true, (0, 0, 0), usize::MAX),
+                        declaration,
+                    ));
+                }
+            }
+            declarations.sort_by_key(|(rank, _)| *rank);
+            let mut winners: Vec<Declaration> = Vec::new();
+            for (_, declaration) in declarations {
+                winners.retain(|d| d.property != declaration.property);
+                winners.push(declaration);
+            }
+            updates.push((
+                node_id,
+                winners
+                    .iter()
+                    .map(|d| d.to_css())
+                    .collect::<Vec<String>>()
+                    .join("; "),
+            ));
+        }
+        for (node_id, style) in updates {
+            crate::ScraperBackend.set_attribute(&mut html, node_id, "style", &style)?;
+        }
+
+        for (i, node_id) in style_elements.into_iter().enumerate() {
+            let mut node = html.tree.get_mut(node_id).unwrap();
+            // the kept rules replace the first stylesheet, without its `media` (they came from all of them)
+            if i == 0 && !kept.is_empty() {
+                let style = scraper::node::Element::new(
+                    QualName::new(None, ns!(html), local_name!("style")),
+                    Vec::new(),
+                );
+                node.insert_before(scraper::Node::Element(style))
+                    .append(scraper::Node::Text(scraper::node::Text {
+                        text: kept.join("\n").into(),
+                    }));
+            }
+            node.detach();
+        }
+
+        Ok(Value::string(html.html(), call.head))
+    }
+}
+
+/// `(important, inline, specificity, source order)`
+type Rank = (bool, bool, (u32, u32, u32), usize);
+
+struct Inliner<'a> {
+    html: &'a Html,
+    order: usize,
+    matched: HashMap<NodeId, Vec<(Rank, Declaration)>>,
+    /// css of the rules which can't be inlined
+    kept: Vec<String>,
+}
+
+impl Inliner<'_> {
+    fn add_rule(&mut self, rule: &Rule, parent_selectors: &[String]) {
+        let (selectors, declarations, rules) = match rule {
+            Rule::Style {
+                selectors,
+                declarations,
+                rules,
+            } => (selectors, declarations, rules),
+            // nested group rule (`.a { @media print { color: red } }`)
+            Rule::At {
+                name,
+                prelude,
+                block: Some((declarations, rules)),
+            } if !parent_selectors.is_empty() => {
+                self.kept.push(
+                    Rule::At {
+                        name: name.clone(),
+                        prelude: prelude.clone(),
+                        block: Some((
+                            Vec::new(),
+                            vec![Rule::Style {
+                                selectors: parent_selectors.to_vec(),
+                                declarations: declarations.clone(),
+                                rules: rules.clone(),
+                            }],
+                        )),
+                    }
+                    .to_css(),
+                );
+                return;
+            }
+            Rule::At { .. } => {
+                self.kept.push(rule.to_css());
+                return;
+            }
+        };
+        self.order += 1;
+        let selectors: Vec<String> = selectors
+            .iter()
+            .map(|s| resolve_nesting(s, parent_selectors))
+            .collect();
+
+        let mut not_inlinable: Vec<String> = Vec::new();
+        for selector in selectors.iter().filter(|_| !declarations.is_empty()) {
+            let specificity = match parse_selector_list(selector).as_deref() {
+                Ok([info]) if !info.dynamic => info.specificity,
+                _ => {
+                    not_inlinable.push(selector.clone());
+                    continue;
+                }
+            };
+            let Ok(parsed) = Selector::parse(selector) else {
+                not_inlinable.push(selector.clone());
+                continue;
+            };
+            for element in self.html.select(&parsed) {
+                let matched = self.matched.entry(element.id()).or_default();
+                for declaration in declarations {
+                    matched.push((
+                        (declaration.important, false, specificity, self.order),
+                        declaration.clone(),
+                    ));
+                }
+            }
+        }
+        if !not_inlinable.is_empty() {
+            self.kept.push(
+                Rule::Style {
+                    selectors: not_inlinable,
+                    declarations: declarations.clone(),
+                    rules: Vec::new(),
+                }
+                .to_css(),
+            );
+        }
+
+        for rule in rules {
+            self.add_rule(rule, &selectors);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Spanned, SyntaxShape, Type, Value};
+use url::Url;
+
+pub struct JoinUrlCommand;
+
+impl SimplePluginCommand for JoinUrlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo url join"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(Type::String, Type::String)
+            .required(
+                "base",
+                SyntaxShape::String,
+                "absolute url to resolve against",
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            resolve a (relative) url against a base url the way browsers resolve links ("../a?b" with base "https://example.com/x/y" -> "https://example.com/a?b").
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let base: Spanned<String> = call.req(0)?;
+        let base = Url::parse(&base.item).map_err(|err| {
+            LabeledError::new("Failed to parse base url").with_label(err.to_string(), base.span)
+        })?;
+        let relative = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let url = base.join(relative).map_err(|err| {
+            LabeledError::new("Failed to join url").with_label(err.to_string(), input.span())
+        })?;
+        Ok(Value::string(url.as_str(), call.head))
+    }
+}
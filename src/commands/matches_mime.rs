@@ -0,0 +1,80 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, SyntaxShape, Type, Value};
+
+use super::mime::MediaType;
+
+pub struct MatchesMimeCommand;
+
+impl SimplePluginCommand for MatchesMimeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime matches"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::Bool),
+                (Type::record(), Type::Bool),
+            ])
+            .required(
+                "pattern",
+                SyntaxShape::String,
+                "mime type to match against, e.g. 'image/*', '+json' or 'text/html; charset=utf-8'",
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            check whether a mime type (string or record in the format of 'servo mime parse') matches a pattern.
+            '*' matches any type or subtype, a '*+json' subtype (or just '+json') matches structured syntax suffixes
+            and the type itself (application/ld+json and application/json).
+            parameters of the pattern have to be present with the same value (charset is compared case-insensitively).
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let mime = MediaType::from_value(input)?;
+        let pattern_value: Value = call.req(0)?;
+        let pattern = pattern_value.as_str()?.trim();
+        let pattern = if pattern.starts_with('+') {
+            MediaType::parse(&format!("*/*{pattern}"), pattern_value.span())?
+        } else {
+            MediaType::parse(pattern, pattern_value.span())?
+        };
+        Ok(Value::bool(mime_matches(&mime, &pattern), call.head))
+    }
+}
+
+pub(crate) fn mime_matches(mime: &MediaType, pattern: &MediaType) -> bool {
+    let type_matches = pattern.type_ == "*" || pattern.type_ == mime.type_;
+    let subtype_matches = match pattern.subtype.strip_prefix("*+") {
+        _ if pattern.subtype == "*" => true,
+        Some(suffix) => {
+            mime.subtype == suffix
+                || mime
+                    .subtype
+                    .strip_suffix(suffix)
+                    .is_some_and(|s| s.ends_with('+'))
+        }
+        None => pattern.subtype == mime.subtype,
+    };
+    type_matches
+        && subtype_matches
+        && pattern.parameters.iter().all(|(name, value)| {
+            mime.parameter(name).is_some_and(|v| {
+                if name == "charset" {
+                    v.eq_ignore_ascii_case(value)
+                } else {
+                    v == value
+                }
+            })
+        })
+}
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use nu_plugin::SimplePluginCommand;
-use nu_protocol::{LabeledError, Record, Signature, Type, Value};
+use nu_protocol::{LabeledError, Record, Signature, Span, Type, Value};
 
 pub struct ParseMimeCommand;
 
@@ -13,14 +13,7 @@ impl SimplePluginCommand for ParseMimeCommand {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::new(self.name()).input_output_type(
-            Type::String,
-            Type::Record(Box::new([
-                (String::from("type"), Type::String),
-                (String::from("subtype"), Type::String),
-                (String::from("parameters"), Type::record()),
-            ])),
-        )
+        Signature::new(self.name()).input_output_type(Type::String, mime_record_type())
     }
 
     fn description(&self) -> &str {
@@ -46,29 +39,193 @@ impl SimplePluginCommand for ParseMimeCommand {
                 return Err(LabeledError::new(format!("Failed to parse mime: {err}")));
             }
         };
-        let span = call.head;
+        Ok(mime_to_record(&m, call.head))
+    }
+}
+
+/// `{type, subtype, parameters}`
+pub(crate) fn mime_to_record(m: &mime::Mime, span: Span) -> Value {
+    MediaType::from_mime(m).to_value(span)
+}
+
+pub(crate) fn mime_record_type() -> Type {
+    Type::Record(Box::new([
+        (String::from("type"), Type::String),
+        (String::from("subtype"), Type::String),
+        (String::from("parameters"), Type::record()),
+    ]))
+}
+
+/// A parsed mime type which (unlike [mime::Mime]) can be built from a record.
+/// Type, subtype and parameter names are lowercase.
+pub(crate) struct MediaType {
+    pub type_: String,
+    /// including the suffix (`svg+xml`)
+    pub subtype: String,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl MediaType {
+    pub(crate) fn from_mime(m: &mime::Mime) -> Self {
+        // the mime crate splits `rss+xml` into subtype and suffix
+        let subtype = match m.suffix() {
+            Some(suffix) => format!("{}+{}", m.subtype(), suffix),
+            None => m.subtype().to_string(),
+        };
+        Self {
+            type_: m.type_().as_str().to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            parameters: m
+                .params()
+                .map(|(k, v)| (k.as_str().to_ascii_lowercase(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn parse(input: &str, span: Span) -> Result<Self, LabeledError> {
+        mime::Mime::from_str(input.trim())
+            .map(|m| Self::from_mime(&m))
+            .map_err(|err| {
+                LabeledError::new(format!("Failed to parse mime: {err}")).with_label("here", span)
+            })
+    }
+
+    /// A string or a record in the format of `servo mime parse`.
+    pub(crate) fn from_value(value: &Value) -> Result<Self, LabeledError> {
+        let record = match value {
+            Value::String { val, .. } => return Self::parse(val, value.span()),
+            Value::Record { val, .. } => val,
+            _ => {
+                return Err(
+                    LabeledError::new("Invalid input (expected string or record)")
+                        .with_label("here", value.span()),
+                );
+            }
+        };
+        let token = |key: &str| -> Result<String, LabeledError> {
+            match record.get(key) {
+                Some(v @ Value::String { val, .. }) => {
+                    if val.is_empty() || !val.bytes().all(is_token) {
+                        return Err(LabeledError::new(format!("Invalid {key} '{val}'"))
+                            .with_label("not a valid token", v.span()));
+                    }
+                    Ok(val.to_ascii_lowercase())
+                }
+                Some(v) => Err(LabeledError::new(format!("Invalid value for '{key}'"))
+                    .with_label(format!("expected string, got {}", v.get_type()), v.span())),
+                None => Err(LabeledError::new(format!("Missing '{key}'"))
+                    .with_label("the mime type needs a type and subtype", value.span())),
+            }
+        };
+        let type_ = token("type")?;
+        let subtype = token("subtype")?;
+
+        let mut parameters = Vec::new();
+        match record.get("parameters") {
+            None | Some(Value::Nothing { .. }) => {}
+            Some(Value::Record { val, .. }) => {
+                for (name, v) in val.iter() {
+                    if name.is_empty() || !name.bytes().all(is_token) {
+                        return Err(
+                            LabeledError::new(format!("Invalid parameter name '{name}'"))
+                                .with_label("not a valid token", v.span()),
+                        );
+                    }
+                    let v_span = v.span();
+                    let v = match v {
+                        Value::String { val, .. } => val.clone(),
+                        Value::Int { val, .. } => val.to_string(),
+                        Value::Bool { val, .. } => val.to_string(),
+                        _ => {
+                            return Err(LabeledError::new(format!(
+                                "Invalid value for parameter '{name}'"
+                            ))
+                            .with_label(
+                                format!("expected string, got {}", v.get_type()),
+                                v.span(),
+                            ));
+                        }
+                    };
+                    if !v.chars().all(is_quoted_string_token) {
+                        return Err(LabeledError::new(format!(
+                            "Invalid value for parameter '{name}'"
+                        ))
+                        .with_label(
+                            "control characters and characters above U+00FF are not allowed",
+                            v_span,
+                        ));
+                    }
+                    parameters.push((name.to_ascii_lowercase(), v));
+                }
+            }
+            Some(v) => {
+                return Err(LabeledError::new("Invalid value for 'parameters'")
+                    .with_label(format!("expected record, got {}", v.get_type()), v.span()));
+            }
+        }
+        Ok(Self {
+            type_,
+            subtype,
+            parameters,
+        })
+    }
+
+    /// `type/subtype` without parameters
+    pub(crate) fn essence(&self) -> String {
+        format!("{}/{}", self.type_, self.subtype)
+    }
+
+    pub(crate) fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
 
+    pub(crate) fn to_value(&self, span: Span) -> Value {
         let mut params = Record::new();
-        for (k, v) in m.params() {
-            params.push(k.to_string(), Value::string(v.to_string(), span));
+        for (k, v) in &self.parameters {
+            params.push(k.clone(), Value::string(v.clone(), span));
         }
 
-        Ok(Value::record(
-            Record::from_raw_cols_vals(
-                vec![
-                    String::from("type"),
-                    String::from("subtype"),
-                    String::from("parameters"),
-                ],
-                vec![
-                    Value::string(m.type_().to_string(), span),
-                    Value::string(m.subtype().to_string(), span),
-                    Value::record(params, span),
-                ],
-                span,
-                span,
-            )?,
-            span,
-        ))
+        let mut record = Record::new();
+        record.push("type", Value::string(self.type_.clone(), span));
+        record.push("subtype", Value::string(self.subtype.clone(), span));
+        record.push("parameters", Value::record(params, span));
+        Value::record(record, span)
     }
 }
+
+/// Serializes as described in <https://mimesniff.spec.whatwg.org/#serializing-a-mime-type>:
+/// parameter values which aren't tokens are quoted.
+impl std::fmt::Display for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.type_, self.subtype)?;
+        for (name, value) in &self.parameters {
+            write!(f, ";{name}=")?;
+            if !value.is_empty() && value.bytes().all(is_token) {
+                f.write_str(value)?;
+            } else {
+                f.write_str("\"")?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                f.write_str("\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// <https://mimesniff.spec.whatwg.org/#http-quoted-string-token-code-point>
+fn is_quoted_string_token(c: char) -> bool {
+    matches!(c, '\t' | ' '..='~' | '\u{80}'..='\u{ff}')
+}
+
+/// <https://mimesniff.spec.whatwg.org/#http-token-code-point>
+fn is_token(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
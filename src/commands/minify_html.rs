@@ -0,0 +1,66 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Type, Value};
+
+use crate::minify::{MinifyOptions, minify_children};
+
+pub struct MinifyHtmlCommand;
+
+impl SimplePluginCommand for MinifyHtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html minify"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::String),
+                (Type::Binary, Type::String),
+            ])
+            .switch(
+                "keep-conditional-comments",
+                "keep <!--[if IE]>...<![endif]--> comments",
+                Some('c'),
+            )
+            .switch(
+                "inline",
+                "minify the content of <style> and <script> elements too",
+                Some('i'),
+            )
+            .switch(
+                "fragment",
+                "the input is an html fragment (don't add <html>, <head> and <body>)",
+                Some('f'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            minify html: collapse insignificant whitespace (<pre> and inline content are respected), remove comments,
+            omit optional tags (</li>, </p>, <head>, ...), unquote attribute values and shorten boolean attributes.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let options = MinifyOptions {
+            keep_conditional_comments: call.has_flag("keep-conditional-comments")?,
+            minify_inline: call.has_flag("inline")?,
+        };
+        let b = crate::ScraperBackend;
+        let out = if call.has_flag("fragment")? {
+            let html = b.parse_fragment(input)?;
+            minify_children(*html.root_element(), &options)
+        } else {
+            let html = b.parse_with_doctype(input)?;
+            minify_children(html.tree.root(), &options)
+        };
+        Ok(Value::string(out, call.head))
+    }
+}
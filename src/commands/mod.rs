@@ -1,13 +1,89 @@
+#[cfg(all(feature = "css", feature = "data_url", feature = "scraper_backend"))]
+pub mod data_urls;
+#[cfg(feature = "scraper_backend")]
+pub mod diff_html;
+#[cfg(feature = "scraper_backend")]
+pub mod format_html;
+#[cfg(all(
+    feature = "css",
+    feature = "data_url",
+    feature = "mime",
+    feature = "scraper_backend"
+))]
+pub mod inline_resources;
+#[cfg(all(feature = "css", feature = "scraper_backend"))]
+pub mod inline_styles;
+#[cfg(feature = "scraper_backend")]
+pub mod minify_html;
 pub mod parse_html;
 pub mod query_html;
+#[cfg(feature = "render")]
+pub mod render_html;
+#[cfg(all(feature = "css", feature = "scraper_backend"))]
+pub mod sanitize_html;
+#[cfg(feature = "scraper_backend")]
+pub mod update_html;
 
+#[cfg(feature = "xml")]
+pub mod diff_xml;
+#[cfg(feature = "xml")]
+pub mod format_xml;
 #[cfg(feature = "xml")]
 pub mod parse_xml;
 #[cfg(feature = "xml")]
 pub mod query_xml;
 
+#[cfg(feature = "url")]
+pub mod join_url;
 #[cfg(feature = "data_url")]
 pub mod parse_data_url;
+#[cfg(feature = "url")]
+pub mod parse_url;
+#[cfg(feature = "data_url")]
+pub mod to_data_url;
+#[cfg(feature = "url")]
+pub mod to_url;
 
+#[cfg(feature = "mime")]
+pub mod extensions_mime;
+#[cfg(feature = "mime")]
+pub mod from_path_mime;
+#[cfg(feature = "mime")]
+pub mod matches_mime;
 #[cfg(feature = "mime")]
 pub mod mime;
+#[cfg(feature = "mime")]
+pub mod negotiate_mime;
+#[cfg(feature = "mime")]
+pub mod parse_multipart;
+#[cfg(feature = "mime")]
+pub mod sniff_mime;
+#[cfg(feature = "mime")]
+pub mod to_mime;
+#[cfg(feature = "mime")]
+pub mod to_multipart;
+
+#[cfg(all(
+    feature = "css",
+    feature = "data_url",
+    feature = "mime",
+    feature = "scraper_backend",
+    feature = "url"
+))]
+pub mod parse_mhtml;
+
+#[cfg(feature = "encoding")]
+pub mod decode;
+#[cfg(feature = "encoding")]
+pub mod encode;
+
+#[cfg(feature = "css")]
+pub mod css_coverage;
+#[cfg(feature = "css")]
+pub mod css_declarations;
+#[cfg(feature = "css")]
+pub mod css_selector;
+#[cfg(feature = "css")]
+pub mod css_value;
+#[cfg(feature = "css")]
+pub mod parse_css;
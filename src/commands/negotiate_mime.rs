@@ -0,0 +1,237 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, SyntaxShape, Type, Value};
+
+use super::matches_mime::mime_matches;
+use super::mime::MediaType;
+
+pub struct NegotiateMimeCommand;
+
+impl SimplePluginCommand for NegotiateMimeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime negotiate"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::record()),
+                (Type::Nothing, Type::record()),
+            ])
+            .required(
+                "offered",
+                SyntaxShape::List(Box::new(SyntaxShape::String)),
+                "the available types (or languages/encodings), most preferred first",
+            )
+            .switch(
+                "language",
+                "the input is an Accept-Language header",
+                Some('l'),
+            )
+            .switch(
+                "encoding",
+                "the input is an Accept-Encoding header",
+                Some('e'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            choose from the offered types using an Accept header (RFC 9110 proactive negotiation).
+            each offer gets the q-value of the most specific matching range (text/html;level=1 > text/html > text/* > */*).
+            offers are ranked by q, ties keep the order of the offers. offers with q=0 are not acceptable.
+            returns the chosen offer (null if none is acceptable) and the ranked table with the range that matched.
+            without input (no Accept header) everything is acceptable.
+            with --language, ranges match language tags by prefix (en matches en-US). with --encoding, identity is
+            acceptable unless it is excluded.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let header = match input {
+            Value::String { val, .. } => Some(val.as_str()),
+            Value::Nothing { .. } => None,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)")
+                    .with_label("here", input.span()));
+            }
+        };
+        let offered: Vec<Value> = call.req(0)?;
+        let language = call.has_flag("language")?;
+        let encoding = call.has_flag("encoding")?;
+        if language && encoding {
+            return Err(LabeledError::new("Incompatible flags")
+                .with_label("use either --language or --encoding", span));
+        }
+
+        let mut ranked = Vec::with_capacity(offered.len());
+        for offer in &offered {
+            let name = offer.as_str()?;
+            let (q, range) = match header {
+                None => (1.0, None),
+                Some(header) if language => rank_language(header, name),
+                Some(header) if encoding => rank_encoding(header, name),
+                Some(header) => rank_media_type(header, &MediaType::parse(name, offer.span())?),
+            };
+            ranked.push((name, q, range));
+        }
+        // stable, so equal q-values keep the order of the offers
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let chosen = ranked
+            .iter()
+            .find(|(_, q, _)| *q > 0.0)
+            .map_or(Value::nothing(span), |(name, _, _)| {
+                Value::string(name.to_string(), span)
+            });
+        let rows = ranked
+            .into_iter()
+            .map(|(name, q, range)| {
+                let mut row = Record::new();
+                row.push("offer", Value::string(name, span));
+                row.push("q", Value::float(q, span));
+                row.push(
+                    "range",
+                    range.map_or(Value::nothing(span), |r| Value::string(r, span)),
+                );
+                Value::record(row, span)
+            })
+            .collect();
+
+        let mut out = Record::new();
+        out.push("chosen", chosen);
+        out.push("ranked", Value::list(rows, span));
+        Ok(Value::record(out, span))
+    }
+}
+
+/// An element of an Accept list: the range (with its other parameters) and the q-value.
+struct Weighted<'a> {
+    range: &'a str,
+    q: f64,
+}
+
+/// Splits `a;q=0.5, b` into its elements. Elements with an invalid q-value are skipped.
+fn parse_accept(header: &str) -> Vec<Weighted<'_>> {
+    let mut out = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    let mut elements = Vec::new();
+    for (i, c) in header.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                elements.push(&header[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elements.push(&header[start..]);
+
+    for element in elements {
+        let element = element.trim();
+        if element.is_empty() {
+            continue;
+        }
+        // the q parameter separates the range from accept extensions
+        let mut range = element;
+        let mut q = Some(1.0);
+        let mut offset = 0;
+        for param in element.split(';') {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            if offset > 0 && name.trim().eq_ignore_ascii_case("q") {
+                range = element[..offset - 1].trim_end();
+                q = parse_qvalue(value.trim());
+                break;
+            }
+            offset += param.len() + 1;
+        }
+        if let Some(q) = q {
+            out.push(Weighted { range, q });
+        }
+    }
+    out
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc9110#name-quality-values>
+fn parse_qvalue(value: &str) -> Option<f64> {
+    let valid = match value.split_once('.') {
+        None => matches!(value, "0" | "1"),
+        Some((int, fraction)) => {
+            matches!(int, "0" | "1")
+                && fraction.len() <= 3
+                && fraction.bytes().all(|b| b.is_ascii_digit())
+                && (int == "0" || fraction.bytes().all(|b| b == b'0'))
+        }
+    };
+    if !valid {
+        return None;
+    }
+    value.parse().ok()
+}
+
+fn rank_media_type(header: &str, offer: &MediaType) -> (f64, Option<String>) {
+    let mut best: Option<((bool, bool, usize), f64, &str)> = None;
+    for Weighted { range, q } in parse_accept(header) {
+        // ranges which can't be parsed are ignored, like in browsers and servers
+        let Ok(parsed) = MediaType::parse(range, Span::unknown()) else {
+            continue;
+        };
+        if !mime_matches(offer, &parsed) {
+            continue;
+        }
+        let specificity = (
+            parsed.type_ != "*",
+            parsed.subtype != "*",
+            parsed.parameters.len(),
+        );
+        if best.is_none_or(|(s, _, _)| specificity > s) {
+            best = Some((specificity, q, range));
+        }
+    }
+    best.map_or((0.0, None), |(_, q, range)| (q, Some(range.to_owned())))
+}
+
+/// Basic filtering (<https://www.rfc-editor.org/rfc/rfc4647#section-3.3.1>): the longest matching range wins.
+fn rank_language(header: &str, offer: &str) -> (f64, Option<String>) {
+    let mut best: Option<(usize, f64, &str)> = None;
+    for Weighted { range, q } in parse_accept(header) {
+        let matches = range == "*"
+            || offer
+                .as_bytes()
+                .get(..range.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(range.as_bytes()))
+                && matches!(offer.as_bytes().get(range.len()), None | Some(b'-'));
+        let specificity = if range == "*" { 0 } else { range.len() };
+        if matches && best.is_none_or(|(s, _, _)| specificity > s) {
+            best = Some((specificity, q, range));
+        }
+    }
+    best.map_or((0.0, None), |(_, q, range)| (q, Some(range.to_owned())))
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc9110#name-accept-encoding>
+fn rank_encoding(header: &str, offer: &str) -> (f64, Option<String>) {
+    let accept = parse_accept(header);
+    if let Some(Weighted { range, q }) = accept.iter().find(|w| w.range.eq_ignore_ascii_case(offer))
+    {
+        return (*q, Some(range.to_string()));
+    }
+    if let Some(Weighted { range, q }) = accept.iter().find(|w| w.range == "*") {
+        return (*q, Some(range.to_string()));
+    }
+    if offer.eq_ignore_ascii_case("identity") {
+        // not listed and not excluded by *;q=0
+        return (1.0, None);
+    }
+    (0.0, None)
+}
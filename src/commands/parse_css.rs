@@ -0,0 +1,56 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Type, Value};
+
+use crate::css::stylesheet::{parse_stylesheet, rules2nu};
+
+pub struct ParseCssCommand;
+
+impl SimplePluginCommand for ParseCssCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo css parse"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name()).input_output_type(
+            Type::String,
+            Type::Record(Box::new([
+                (String::from("rules"), Type::list(Type::record())),
+                (String::from("errors"), Type::list(Type::record())),
+            ])),
+        )
+    }
+
+    fn description(&self) -> &str {
+        "parse a stylesheet into a list of rules (invalid parts are skipped and listed in 'errors')"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let input: &String = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let span = call.head;
+        let stylesheet = parse_stylesheet(input);
+
+        let mut out = Record::new();
+        out.push("rules", rules2nu(&stylesheet.rules, span));
+        out.push(
+            "errors",
+            Value::list(
+                stylesheet.errors.iter().map(|e| e.to_nu(span)).collect(),
+                span,
+            ),
+        );
+        Ok(Value::record(out, span))
+    }
+}
@@ -1,6 +1,9 @@
 use data_url::DataUrl;
+use data_url::mime::Mime;
 use nu_plugin::SimplePluginCommand;
-use nu_protocol::{LabeledError, Record, Signature, Type, Value};
+use nu_protocol::{LabeledError, Record, Signature, Span, Type, Value};
+
+use crate::codec::percent_decode;
 
 pub struct ParseDataUrlCommand;
 
@@ -24,14 +27,18 @@ impl SimplePluginCommand for ParseDataUrlCommand {
                     ])),
                 ),
                 (String::from("body"), Type::Binary),
+                (String::from("text"), Type::Any),
+                (String::from("base64"), Type::Bool),
                 (String::from("fragment"), Type::Any),
+                (String::from("fragment_decoded"), Type::Any),
             ])),
         )
     }
 
     fn description(&self) -> &str {
         r#"
-            fragment is either null or a percent-encoded string (plugins can't annotate "oneof"? and for some reason i the crate does not expose the not precent-encoded one)
+            text is the body decoded using the charset parameter for textual mime types (text/*, json, xml, javascript), null otherwise.
+            fragment is percent-encoded (as in the url), fragment_decoded is percent-decoded. both are null without fragment.
         "#
     }
 
@@ -42,66 +49,108 @@ impl SimplePluginCommand for ParseDataUrlCommand {
         call: &nu_plugin::EvaluatedCall,
         input: &Value,
     ) -> Result<Value, LabeledError> {
-        let input: &String = match input {
+        let input_str: &String = match input {
             Value::String { val, .. } => val,
             _ => {
                 return Err(LabeledError::new("Invalid input (expected string)"));
             }
         };
-        let url = match DataUrl::process(input) {
-            Ok(v) => v,
-            Err(err) => {
-                return Err(LabeledError::new(format!(
-                    "Failed to parse data-url: {err}"
-                )));
-            }
-        };
-        let (body, fragment) = url.decode_to_vec().unwrap();
-        let mime = url.mime_type();
-
         let span = call.head;
-
-        let mut params = Record::new();
-        for (k, v) in mime.parameters.iter() {
-            params.push(k.clone(), Value::string(v.clone(), span));
-        }
-
         Ok(Value::record(
-            Record::from_raw_cols_vals(
-                vec![
-                    String::from("mime"),
-                    String::from("body"),
-                    String::from("fragment"),
-                ],
-                vec![
-                    Value::record(
-                        Record::from_raw_cols_vals(
-                            vec![
-                                String::from("type"),
-                                String::from("subtype"),
-                                String::from("parameters"),
-                            ],
-                            vec![
-                                Value::string(mime.type_.clone(), span),
-                                Value::string(mime.subtype.clone(), span),
-                                Value::record(params, span),
-                            ],
-                            span,
-                            span,
-                        )?,
-                        span,
-                    ),
-                    Value::binary(body, span),
-                    (if let Some(i) = fragment {
-                        Value::string(i.to_percent_encoded(), span)
-                    } else {
-                        Value::nothing(span)
-                    }),
-                ],
-                span,
-                span,
-            )?,
+            parse_data_url(input_str, input.span(), span)?,
             span,
         ))
     }
 }
+
+/// Parses a data: url into the record returned by `servo data-url parse`,
+/// errors are labeled with `input_span`.
+pub(crate) fn parse_data_url(
+    input: &str,
+    input_span: Span,
+    span: Span,
+) -> Result<Record, LabeledError> {
+    let url = DataUrl::process(input).map_err(|err| {
+        LabeledError::new("Failed to parse data-url").with_label(err.to_string(), input_span)
+    })?;
+    let (body, fragment) = url.decode_to_vec().map_err(|err| {
+        LabeledError::new("Failed to decode data-url")
+            .with_label(format!("invalid base64: {err}"), input_span)
+    })?;
+    let mime = url.mime_type();
+
+    let mut params = Record::new();
+    for (k, v) in mime.parameters.iter() {
+        params.push(k.clone(), Value::string(v.clone(), span));
+    }
+    let mut mime_record = Record::new();
+    mime_record.push("type", Value::string(mime.type_.clone(), span));
+    mime_record.push("subtype", Value::string(mime.subtype.clone(), span));
+    mime_record.push("parameters", Value::record(params, span));
+
+    let mut record = Record::new();
+    record.push("mime", Value::record(mime_record, span));
+    let text = match decode_text(mime, &body) {
+        Some(text) => Value::string(text, span),
+        None => Value::nothing(span),
+    };
+    record.push("body", Value::binary(body, span));
+    record.push("text", text);
+    record.push("base64", Value::bool(is_base64(input), span));
+    let fragment = fragment.map(|f| f.to_percent_encoded());
+    let fragment_decoded = fragment
+        .as_ref()
+        .map(|f| String::from_utf8_lossy(&percent_decode(f)).into_owned());
+    for (key, value) in [
+        ("fragment", fragment),
+        ("fragment_decoded", fragment_decoded),
+    ] {
+        record.push(
+            key,
+            value.map_or(Value::nothing(span), |v| Value::string(v, span)),
+        );
+    }
+    Ok(record)
+}
+
+fn is_textual(mime: &Mime) -> bool {
+    mime.type_ == "text"
+        || mime.subtype.ends_with("+xml")
+        || mime.subtype.ends_with("+json")
+        || (mime.type_ == "application"
+            && matches!(
+                mime.subtype.as_str(),
+                "json" | "xml" | "javascript" | "ecmascript" | "x-www-form-urlencoded"
+            ))
+}
+
+/// Decodes textual bodies according to their charset.
+/// The implicit `text/plain;charset=US-ASCII` of data: urls without a mime type is decoded
+/// as windows-1252 (like browsers do), other types without a charset (json, ...) as UTF-8.
+fn decode_text(mime: &Mime, body: &[u8]) -> Option<String> {
+    if !is_textual(mime) {
+        return None;
+    }
+    let encoding = match mime.get_parameter("charset") {
+        Some(label) => {
+            encoding_rs::Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::WINDOWS_1252)
+        }
+        None => encoding_rs::UTF_8,
+    };
+    let (text, _, _) = encoding.decode(body);
+    Some(text.into_owned())
+}
+
+/// Whether the (valid) data: url uses `;base64`.
+fn is_base64(url: &str) -> bool {
+    let Some((header, _)) = url.split_once(',') else {
+        return false;
+    };
+    let header = header.trim_end_matches(|c: char| c.is_ascii_whitespace());
+    header.len() >= 6
+        && header.is_char_boundary(header.len() - 6)
+        && header[header.len() - 6..].eq_ignore_ascii_case("base64")
+        && header[..header.len() - 6]
+            .trim_end_matches(' ')
+            .ends_with(';')
+}
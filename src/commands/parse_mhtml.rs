@@ -0,0 +1,385 @@
+use std::str::FromStr;
+
+use data_url::mime::Mime;
+use ego_tree::NodeId;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, Type, Value};
+use scraper::{Html, Node};
+use url::Url;
+
+use super::mime::MediaType;
+use super::to_data_url::encode_data_url;
+use crate::HtmlBackendMut;
+use crate::codec::percent_decode;
+use crate::css::urls::rewrite_urls;
+use crate::multipart::{Part, decode_transfer_encoding, parse_message, parse_multipart};
+use crate::srcset::{parse_srcset, serialize_srcset};
+
+pub struct ParseMhtmlCommand;
+
+impl SimplePluginCommand for ParseMhtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mhtml parse"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::Binary, Type::record()),
+                (Type::String, Type::record()),
+            ])
+            .switch(
+                "data-urls",
+                "replace references to resources of the archive in the html (and its css) with data: urls",
+                Some('d'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            split a saved web page (.mhtml/.mht, multipart/related) into its resources.
+            returns the headers of the archive, the url and decoded html of the main document
+            (the start parameter or the first part) and the resources (url from Content-Location, content_id,
+            content_type and the body with base64/quoted-printable decoded).
+            with --data-urls, src, href, srcset, poster, url()s and cid: references which resolve to a resource
+            are replaced with data: urls, so the html can be used without the archive.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let bytes: &[u8] = match input {
+            Value::Binary { val, .. } => val,
+            Value::String { val, .. } => val.as_bytes(),
+            _ => {
+                return Err(
+                    LabeledError::new("Invalid input (expected binary or string)")
+                        .with_label("here", input.span()),
+                );
+            }
+        };
+        let message = parse_message(bytes);
+        let content_type = message
+            .header("content-type")
+            .and_then(|value| MediaType::parse(value, span).ok());
+
+        let mut resources = Vec::new();
+        let mut main = 0;
+        match content_type
+            .as_ref()
+            .filter(|mime| mime.type_ == "multipart")
+            .and_then(|mime| mime.parameter("boundary"))
+        {
+            Some(boundary) => {
+                let parts = parse_multipart(&message.body, boundary).map_err(|err| {
+                    LabeledError::new("Failed to parse mhtml").with_label(err, input.span())
+                })?;
+                collect_resources(parts, &mut resources);
+                let start = content_type
+                    .as_ref()
+                    .and_then(|mime| mime.parameter("start"))
+                    .map(content_id);
+                if let Some(start) = start {
+                    main = resources
+                        .iter()
+                        .position(|r| r.content_id.as_deref() == Some(start.as_str()))
+                        .unwrap_or(0);
+                }
+            }
+            // a single document
+            None => collect_resources(
+                vec![Part {
+                    headers: message.headers.clone(),
+                    body: message.body.clone(),
+                }],
+                &mut resources,
+            ),
+        }
+        let Some(document) = resources.get(main) else {
+            return Err(LabeledError::new("Failed to parse mhtml")
+                .with_label("the archive has no parts", input.span()));
+        };
+
+        let archive = Archive {
+            resources: &resources,
+        };
+        let html = if call.has_flag("data-urls")? {
+            archive.rewrite_html(main, &[])?
+        } else {
+            document.text()
+        };
+
+        let optional = |s: &Option<String>| {
+            s.as_ref()
+                .map_or(Value::nothing(span), |s| Value::string(s, span))
+        };
+        let headers = message
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let mut row = Record::new();
+                row.push("name", Value::string(name, span));
+                row.push("value", Value::string(value, span));
+                Value::record(row, span)
+            })
+            .collect();
+        let resource_rows = resources
+            .iter()
+            .map(|resource| {
+                let mut row = Record::new();
+                row.push("url", optional(&resource.url));
+                row.push("content_id", optional(&resource.content_id));
+                row.push(
+                    "content_type",
+                    resource
+                        .mime
+                        .as_ref()
+                        .map_or(Value::nothing(span), |mime| mime.to_value(span)),
+                );
+                row.push("body", Value::binary(resource.body.clone(), span));
+                Value::record(row, span)
+            })
+            .collect();
+
+        let mut record = Record::new();
+        record.push("headers", Value::list(headers, span));
+        record.push("url", optional(&document.url));
+        record.push("html", Value::string(html, span));
+        record.push("resources", Value::list(resource_rows, span));
+        Ok(Value::record(record, span))
+    }
+}
+
+struct Resource {
+    url: Option<String>,
+    content_id: Option<String>,
+    /// the Content-Type as it is
+    content_type: Option<String>,
+    mime: Option<MediaType>,
+    /// decoded
+    body: Vec<u8>,
+}
+
+impl Resource {
+    fn base(&self) -> Option<Url> {
+        self.url.as_deref().and_then(|url| Url::parse(url).ok())
+    }
+
+    fn essence(&self) -> Option<String> {
+        self.mime.as_ref().map(MediaType::essence)
+    }
+
+    /// The body decoded using the charset (UTF-8 by default, a BOM wins).
+    fn text(&self) -> String {
+        let encoding = self
+            .mime
+            .as_ref()
+            .and_then(|mime| mime.parameter("charset"))
+            .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+            .unwrap_or(encoding_rs::UTF_8);
+        let (text, _, _) = encoding.decode(&self.body);
+        text.into_owned()
+    }
+}
+
+/// Adds the parts as resources, the parts of nested multiparts are added instead of them.
+fn collect_resources(parts: Vec<Part>, resources: &mut Vec<Resource>) {
+    for part in parts {
+        let mime = part
+            .header("content-type")
+            .and_then(|value| MediaType::parse(value, Span::unknown()).ok());
+        if let Some(boundary) = mime
+            .as_ref()
+            .filter(|mime| mime.type_ == "multipart")
+            .and_then(|mime| mime.parameter("boundary"))
+            && let Ok(nested) = parse_multipart(&part.body, boundary)
+        {
+            collect_resources(nested, resources);
+            continue;
+        }
+        resources.push(Resource {
+            url: part.header("content-location").map(str::to_owned),
+            content_id: part.header("content-id").map(content_id),
+            content_type: part.header("content-type").map(str::to_owned),
+            mime,
+            body: decode_transfer_encoding(&part.body, part.header("content-transfer-encoding")),
+        });
+    }
+}
+
+/// `<id@host>` -> `id@host`
+fn content_id(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('<')
+        .and_then(|v| v.strip_suffix('>'))
+        .unwrap_or(value)
+        .to_owned()
+}
+
+struct Archive<'a> {
+    resources: &'a [Resource],
+}
+
+impl Archive<'_> {
+    /// The resource a reference (relative to `base`) points to.
+    fn find(&self, reference: &str, base: Option<&Url>) -> Option<usize> {
+        let reference = reference.trim();
+        if reference
+            .get(..4)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("cid:"))
+        {
+            let id = String::from_utf8_lossy(&percent_decode(&reference[4..])).into_owned();
+            return self
+                .resources
+                .iter()
+                .position(|r| r.content_id.as_deref() == Some(id.as_str()));
+        }
+        let without_fragment = |mut url: Url| {
+            url.set_fragment(None);
+            url
+        };
+        let url = match base {
+            Some(base) => base.join(reference),
+            None => Url::parse(reference),
+        }
+        .ok()
+        .map(without_fragment);
+        self.resources.iter().position(|r| {
+            let Some(location) = r.url.as_deref() else {
+                return false;
+            };
+            location == reference
+                || url.is_some() && Url::parse(location).ok().map(without_fragment) == url
+        })
+    }
+
+    /// The `data:` url of a resource (with its own references replaced).
+    /// `ancestors` are the documents and stylesheets containing the reference.
+    fn data_url(&self, index: usize, ancestors: &[usize]) -> Result<String, LabeledError> {
+        let resource = &self.resources[index];
+        let mime = resource
+            .content_type
+            .as_deref()
+            .and_then(|value| Mime::from_str(value).ok())
+            .unwrap_or_else(|| {
+                Mime::from_str("application/octet-stream").expect("valid mime type")
+            });
+        let text = match resource.essence().as_deref() {
+            Some("text/css") => Some(self.rewrite_css(
+                &resource.text(),
+                resource.base().as_ref(),
+                &[ancestors, &[index]].concat(),
+            )),
+            Some("text/html") => Some(self.rewrite_html(index, ancestors)?),
+            _ => None,
+        };
+        let Some(text) = text else {
+            return Ok(encode_data_url(&mime, &resource.body, None));
+        };
+        // the text was decoded, so it is utf-8 now
+        let mime = Mime {
+            parameters: mime
+                .parameters
+                .iter()
+                .filter(|(name, _)| name != "charset")
+                .cloned()
+                .chain([(String::from("charset"), String::from("utf-8"))])
+                .collect(),
+            ..mime
+        };
+        Ok(encode_data_url(&mime, text.as_bytes(), None))
+    }
+
+    /// The data: url for a reference, `None` for references outside of the archive
+    /// and to the ancestors (which would be a cycle).
+    fn replace(&self, reference: &str, base: Option<&Url>, ancestors: &[usize]) -> Option<String> {
+        let index = self.find(reference, base)?;
+        if ancestors.contains(&index) {
+            return None;
+        }
+        let mut data_url = self.data_url(index, ancestors).ok()?;
+        if let Some((_, fragment)) = reference.split_once('#') {
+            data_url.push('#');
+            data_url.push_str(fragment);
+        }
+        Some(data_url)
+    }
+
+    fn rewrite_css(&self, css: &str, base: Option<&Url>, ancestors: &[usize]) -> String {
+        rewrite_urls(css, &mut |url| self.replace(url, base, ancestors))
+    }
+
+    /// The html of a resource with its references replaced.
+    fn rewrite_html(&self, index: usize, ancestors: &[usize]) -> Result<String, LabeledError> {
+        let ancestors = &[ancestors, &[index]].concat();
+        let b = crate::ScraperBackend;
+        let resource = &self.resources[index];
+        let mut html = b.parse_with_doctype(&Value::string(resource.text(), Span::unknown()))?;
+        let base = resource.base();
+        let base = base.as_ref();
+
+        let elements: Vec<NodeId> = html
+            .tree
+            .nodes()
+            .filter(|n| n.value().is_element())
+            .map(|n| n.id())
+            .collect();
+        for id in elements {
+            let Some(Node::Element(element)) = html.tree.get(id).map(|n| n.value()) else {
+                continue;
+            };
+            let tag = element.name.local.clone();
+            let mut attributes = vec!["src", "poster", "background", "data"];
+            if &*tag == "link" {
+                attributes.push("href");
+            }
+            for attribute in attributes {
+                let Some(value) = b.get_attribute(&html, id, attribute) else {
+                    continue;
+                };
+                if let Some(new) = self.replace(&value, base, ancestors) {
+                    b.set_attribute(&mut html, id, attribute, &new)?;
+                }
+            }
+            for attribute in ["srcset", "imagesrcset"] {
+                let Some(value) = b.get_attribute(&html, id, attribute) else {
+                    continue;
+                };
+                let mut candidates = parse_srcset(&value);
+                for candidate in candidates.iter_mut() {
+                    if let Some(new) = self.replace(&candidate.url, base, ancestors) {
+                        candidate.url = new;
+                    }
+                }
+                b.set_attribute(&mut html, id, attribute, &serialize_srcset(&candidates))?;
+            }
+            if let Some(style) = b.get_attribute(&html, id, "style") {
+                let style = self.rewrite_css(&style, base, ancestors);
+                b.set_attribute(&mut html, id, "style", &style)?;
+            }
+            if &*tag == "style" {
+                let css = style_text(&html, id);
+                b.set_text(&mut html, id, &self.rewrite_css(&css, base, ancestors))?;
+            }
+        }
+        b.document_html(&html)
+    }
+}
+
+fn style_text(html: &Html, id: NodeId) -> String {
+    html.tree
+        .get(id)
+        .into_iter()
+        .flat_map(|n| n.children())
+        .filter_map(|c| c.value().as_text().map(|t| t.to_string()))
+        .collect()
+}
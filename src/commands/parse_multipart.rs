@@ -0,0 +1,139 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, SyntaxShape, Type, Value};
+
+use super::mime::MediaType;
+use crate::multipart::{Part, parse_disposition, parse_multipart};
+
+pub struct ParseMultipartCommand;
+
+impl SimplePluginCommand for ParseMultipartCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime multipart parse"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::Binary, Type::table()),
+                (Type::String, Type::table()),
+            ])
+            .required(
+                "boundary",
+                SyntaxShape::String,
+                "the boundary or a Content-Type with a boundary parameter",
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            split a multipart body (multipart/form-data, multipart/mixed, multipart/related, ...) into its parts.
+            each part has its headers (table of name/value), the parsed content_type (null without Content-Type),
+            the Content-Disposition type, name and filename (null if missing) and the body as binary.
+            parts of nested multiparts are in parts (null for other parts).
+            transfer encodings (base64, quoted-printable) are not decoded.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let bytes: &[u8] = match input {
+            Value::Binary { val, .. } => val,
+            Value::String { val, .. } => val.as_bytes(),
+            _ => {
+                return Err(
+                    LabeledError::new("Invalid input (expected binary or string)")
+                        .with_label("here", input.span()),
+                );
+            }
+        };
+        let boundary = boundary(&call.req(0)?)?;
+        let parts = parse_multipart(bytes, &boundary).map_err(|err| {
+            LabeledError::new("Failed to parse multipart body").with_label(err, input.span())
+        })?;
+        Ok(parts_to_value(&parts, span))
+    }
+}
+
+/// The boundary of a boundary (`----abc`) or Content-Type (`multipart/form-data; boundary=----abc`) argument.
+pub(crate) fn boundary(value: &Value) -> Result<String, LabeledError> {
+    let input = value.as_str()?.trim();
+    let mut boundary = input.to_owned();
+    // boundaries can contain `/` but not `;`
+    if let Ok(mime) = MediaType::parse(input, value.span()) {
+        if let Some(parameter) = mime.parameter("boundary") {
+            boundary = parameter.to_owned();
+        } else if input.contains(';') || mime.type_ == "multipart" {
+            return Err(LabeledError::new("Missing boundary")
+                .with_label("the Content-Type has no boundary parameter", value.span()));
+        }
+    }
+    // https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1
+    if boundary.is_empty() || boundary.len() > 70 {
+        return Err(LabeledError::new("Invalid boundary")
+            .with_label("boundaries have 1 to 70 characters", value.span()));
+    }
+    let is_bchar = |c: char| c.is_ascii_alphanumeric() || "'()+_,-./:=? ".contains(c);
+    if !boundary.chars().all(is_bchar) || boundary.ends_with(' ') {
+        return Err(LabeledError::new("Invalid boundary")
+            .with_label(
+                "boundaries consist of letters, digits, spaces and '()+_,-./:=? (not ending in a space)",
+                value.span(),
+            ));
+    }
+    Ok(boundary)
+}
+
+fn parts_to_value(parts: &[Part], span: Span) -> Value {
+    Value::list(parts.iter().map(|p| part_to_value(p, span)).collect(), span)
+}
+
+fn part_to_value(part: &Part, span: Span) -> Value {
+    let optional = |s: Option<String>| s.map_or(Value::nothing(span), |s| Value::string(s, span));
+    let headers = part
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let mut row = Record::new();
+            row.push("name", Value::string(name, span));
+            row.push("value", Value::string(value, span));
+            Value::record(row, span)
+        })
+        .collect();
+    let content_type = part
+        .header("content-type")
+        .and_then(|value| MediaType::parse(value, span).ok());
+    let disposition = part.header("content-disposition").map(parse_disposition);
+    let nested = content_type
+        .as_ref()
+        .filter(|mime| mime.type_ == "multipart")
+        .and_then(|mime| mime.parameter("boundary"))
+        .and_then(|boundary| parse_multipart(&part.body, boundary).ok());
+
+    let mut record = Record::new();
+    record.push("headers", Value::list(headers, span));
+    record.push(
+        "content_type",
+        content_type.map_or(Value::nothing(span), |mime| mime.to_value(span)),
+    );
+    let (kind, name, filename) = match disposition {
+        Some(d) => (Some(d.kind), d.name, d.filename),
+        None => (None, None, None),
+    };
+    record.push("disposition", optional(kind));
+    record.push("name", optional(name));
+    record.push("filename", optional(filename));
+    record.push("body", Value::binary(part.body.clone(), span));
+    record.push(
+        "parts",
+        nested.map_or(Value::nothing(span), |parts| parts_to_value(&parts, span)),
+    );
+    Value::record(record, span)
+}
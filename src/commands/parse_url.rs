@@ -0,0 +1,125 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, Type, Value};
+use url::{Host, Url};
+
+pub struct ParseUrlCommand;
+
+impl SimplePluginCommand for ParseUrlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo url parse"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name()).input_output_type(
+            Type::String,
+            Type::Record(Box::new([
+                (String::from("href"), Type::String),
+                (String::from("scheme"), Type::String),
+                (String::from("username"), Type::String),
+                (String::from("password"), Type::Any),
+                (String::from("host"), Type::Any),
+                (String::from("host_type"), Type::Any),
+                (String::from("port"), Type::Any),
+                (String::from("path"), Type::Any),
+                (String::from("query"), Type::Any),
+                (
+                    String::from("params"),
+                    Type::Table(Box::new([
+                        (String::from("key"), Type::String),
+                        (String::from("value"), Type::String),
+                    ])),
+                ),
+                (String::from("fragment"), Type::Any),
+                (String::from("origin"), Type::String),
+            ])),
+        )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            parse an absolute url the way browsers do (WHATWG URL standard: IDNA hosts, special schemes, percent-encoding).
+            href is the normalized url, host_type is domain, ipv4 or ipv6 (null without host).
+            port is null for the default port of the scheme.
+            path is a list of (percent-encoded) segments, or a string for urls without hierarchy (such as mailto:).
+            query and fragment are percent-encoded strings (null if absent), params are the decoded query pairs.
+            origin is "null" for opaque origins (file:, data:, ...).
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let url = parse_url(input)?;
+        Ok(url_to_record(&url, call.head))
+    }
+}
+
+pub(crate) fn parse_url(input: &Value) -> Result<Url, LabeledError> {
+    let input_str = match input {
+        Value::String { val, .. } => val,
+        _ => {
+            return Err(LabeledError::new("Invalid input (expected string)"));
+        }
+    };
+    Url::parse(input_str).map_err(|err| {
+        LabeledError::new("Failed to parse url").with_label(err.to_string(), input.span())
+    })
+}
+
+fn url_to_record(url: &Url, span: Span) -> Value {
+    let optional = |s: Option<&str>| s.map_or(Value::nothing(span), |s| Value::string(s, span));
+
+    let mut record = Record::new();
+    record.push("href", Value::string(url.as_str(), span));
+    record.push("scheme", Value::string(url.scheme(), span));
+    record.push("username", Value::string(url.username(), span));
+    record.push("password", optional(url.password()));
+    record.push("host", optional(url.host_str()));
+    record.push(
+        "host_type",
+        optional(url.host().map(|host| match host {
+            Host::Domain(_) => "domain",
+            Host::Ipv4(_) => "ipv4",
+            Host::Ipv6(_) => "ipv6",
+        })),
+    );
+    record.push(
+        "port",
+        url.port()
+            .map_or(Value::nothing(span), |p| Value::int(p.into(), span)),
+    );
+    record.push(
+        "path",
+        match url.path_segments() {
+            Some(segments) => Value::list(segments.map(|s| Value::string(s, span)).collect(), span),
+            None => Value::string(url.path(), span),
+        },
+    );
+    record.push("query", optional(url.query()));
+    record.push(
+        "params",
+        Value::list(
+            url.query_pairs()
+                .map(|(key, value)| {
+                    let mut pair = Record::new();
+                    pair.push("key", Value::string(key, span));
+                    pair.push("value", Value::string(value, span));
+                    Value::record(pair, span)
+                })
+                .collect(),
+            span,
+        ),
+    );
+    record.push("fragment", optional(url.fragment()));
+    record.push(
+        "origin",
+        Value::string(url.origin().ascii_serialization(), span),
+    );
+    Value::record(record, span)
+}
@@ -20,10 +20,24 @@ impl SimplePluginCommand for QueryXmlCommand {
             ])
             .required("css_selector", SyntaxShape::String, "css selector")
             .named("format", SyntaxShape::String, "", None)
+            .named(
+                "ns",
+                SyntaxShape::OneOf(vec![
+                    SyntaxShape::Record(vec![]),
+                    SyntaxShape::List(Box::new(SyntaxShape::String)),
+                    SyntaxShape::String,
+                ]),
+                "namespace prefixes for the selector: 'prefix=uri' ('=uri' for the default namespace), a list of them or record<prefix: uri>",
+                None,
+            )
     }
 
     fn description(&self) -> &str {
-        ""
+        r#"
+            with --ns, css namespace selectors can be used: --ns atom=http://www.w3.org/2005/Atom 'atom|entry'.
+            the prefixes of the selector are independent of the prefixes used in the document.
+            with a default namespace (--ns =uri), type selectors without prefix only match elements in it.
+        "#
     }
 
     fn run(
@@ -39,8 +53,13 @@ impl SimplePluginCommand for QueryXmlCommand {
         let b = crate::ScraperBackend;
 
         let xml = b.parse_xml(input)?;
+        let namespaces = match call.get_flag_value("ns") {
+            Some(ns) => namespaces(&ns)?,
+            None => Vec::new(),
+        };
+        let nodes = b.css_query_with_namespaces(&xml, &selector, &namespaces)?;
         Ok(Value::list(
-            b.css_query(&xml, &selector)?
+            nodes
                 .iter()
                 .map(|node| -> Result<Value, LabeledError> {
                     b.node2nu(&xml, *node, format, call.head)
@@ -50,3 +69,21 @@ impl SimplePluginCommand for QueryXmlCommand {
         ))
     }
 }
+
+/// `(prefix, uri)` pairs from `prefix=uri`, a list of them or `record<prefix: uri>`.
+fn namespaces(value: &Value) -> Result<Vec<(String, String)>, LabeledError> {
+    let binding = |value: &Value| -> Result<(String, String), LabeledError> {
+        let (prefix, uri) = value.as_str()?.split_once('=').ok_or_else(|| {
+            LabeledError::new("Invalid namespace").with_label("expected prefix=uri", value.span())
+        })?;
+        Ok((prefix.trim().to_owned(), uri.trim().to_owned()))
+    };
+    match value {
+        Value::Record { val, .. } => val
+            .iter()
+            .map(|(prefix, uri)| Ok((prefix.clone(), uri.as_str()?.to_owned())))
+            .collect(),
+        Value::List { vals, .. } => vals.iter().map(binding).collect(),
+        _ => Ok(vec![binding(value)?]),
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::{Arc, Mutex};
+
+use blitz_dom::net::Resource;
+use blitz_dom::{BaseDocument, DocumentConfig};
+use blitz_html::HtmlDocument;
+use blitz_traits::net::{BoxedHandler, NetCallback, NetProvider, Request};
+use blitz_traits::shell::{ColorScheme, Viewport};
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, SyntaxShape, Type, Value};
+
+/// 8192x8192 (256 MB as RGBA)
+const MAX_PIXELS: u64 = 8192 * 8192;
+
+pub struct RenderHtmlCommand;
+
+impl SimplePluginCommand for RenderHtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html render"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![(Type::String, Type::Binary)])
+            .named(
+                "width",
+                SyntaxShape::Int,
+                "viewport width (default: 800)",
+                None,
+            )
+            .named(
+                "height",
+                SyntaxShape::Int,
+                "viewport height (default: 600)",
+                None,
+            )
+            .named(
+                "scale",
+                SyntaxShape::Number,
+                "device pixel ratio (default: 1)",
+                None,
+            )
+            .named(
+                "selector",
+                SyntaxShape::String,
+                "clip the screenshot to the bounding box of the first match",
+                None,
+            )
+    }
+
+    fn description(&self) -> &str {
+        "render a html document on the CPU into a PNG (only local resources: inline styles, data-urls, system fonts)"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let input: &String = match input {
+            Value::String { val, .. } => val,
+            _ => {
+                return Err(LabeledError::new("Invalid input (expected string)"));
+            }
+        };
+        let width: u32 = get_dimension(call, "width", 800)?;
+        let height: u32 = get_dimension(call, "height", 600)?;
+        let scale: f64 = call.get_flag::<f64>("scale")?.unwrap_or(1.0);
+        if scale <= 0.0 {
+            return Err(LabeledError::new("'--scale' has to be positive"));
+        }
+        let selector: Option<String> = call.get_flag("selector")?;
+
+        let pixel_width = (width as f64 * scale).round() as u32;
+        let pixel_height = (height as f64 * scale).round() as u32;
+        if pixel_width == 0
+            || pixel_height == 0
+            || pixel_width as u64 * pixel_height as u64 > MAX_PIXELS
+        {
+            return Err(LabeledError::new(format!(
+                "The screenshot would be {pixel_width}x{pixel_height} pixels"
+            ))
+            .with_label("too large", call.head)
+            .with_help(format!(
+                "'--width' * '--height' * '--scale'² can be at most {MAX_PIXELS} pixels"
+            )));
+        }
+
+        let provider = Arc::new(DataUrlNetProvider::default());
+        let mut html = HtmlDocument::from_html(
+            input,
+            DocumentConfig {
+                viewport: Some(Viewport::new(
+                    width,
+                    height,
+                    scale as f32,
+                    ColorScheme::Light,
+                )),
+                net_provider: Some(provider.clone()),
+                ..Default::default()
+            },
+        );
+        // loading a resource can request more (e.g. the @imports of a stylesheet)
+        loop {
+            let resources = provider.take_resources();
+            if resources.is_empty() {
+                break;
+            }
+            for resource in resources {
+                html.load_resource(resource);
+            }
+        }
+        html.resolve(0.0);
+        let doc: &BaseDocument = &html;
+
+        let buffer = anyrender::render_to_buffer::<anyrender_vello_cpu::VelloCpuImageRenderer, _>(
+            |scene| blitz_paint::paint_scene(scene, doc, scale, pixel_width, pixel_height),
+            pixel_width,
+            pixel_height,
+        );
+
+        let (buffer, out_width, out_height) = match selector {
+            Some(selector) => {
+                let node_id = match doc.query_selector(&selector) {
+                    Ok(Some(v)) => v,
+                    Ok(None) => {
+                        return Err(LabeledError::new(format!(
+                            "No element matches '{selector}'"
+                        )));
+                    }
+                    Err(err) => {
+                        return Err(LabeledError::new(format!("Invalid css: {err:?}")));
+                    }
+                };
+                let node = doc.get_node(node_id).unwrap();
+                let pos = node.absolute_position(0.0, 0.0);
+                let size = node.final_layout.size;
+                crop_rgba(
+                    &buffer,
+                    pixel_width,
+                    pixel_height,
+                    (pos.x as f64 * scale).floor() as i64,
+                    (pos.y as f64 * scale).floor() as i64,
+                    (size.width as f64 * scale).ceil() as i64,
+                    (size.height as f64 * scale).ceil() as i64,
+                )?
+            }
+            None => (buffer, pixel_width, pixel_height),
+        };
+
+        Ok(Value::binary(
+            encode_png(&buffer, out_width, out_height)?,
+            call.head,
+        ))
+    }
+}
+
+fn get_dimension(
+    call: &nu_plugin::EvaluatedCall,
+    name: &str,
+    default: u32,
+) -> Result<u32, LabeledError> {
+    match call.get_flag::<i64>(name)? {
+        None => Ok(default),
+        Some(v) if v > 0 && v <= u16::MAX as i64 => Ok(v as u32),
+        Some(_) => Err(LabeledError::new(format!(
+            "'--{name}' has to be between 1 and {}",
+            u16::MAX
+        ))),
+    }
+}
+
+fn crop_rgba(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    x: i64,
+    y: i64,
+    w: i64,
+    h: i64,
+) -> Result<(Vec<u8>, u32, u32), LabeledError> {
+    let x0 = x.clamp(0, width as i64) as usize;
+    let y0 = y.clamp(0, height as i64) as usize;
+    let x1 = (x + w).clamp(0, width as i64) as usize;
+    let y1 = (y + h).clamp(0, height as i64) as usize;
+    if x1 <= x0 || y1 <= y0 {
+        return Err(LabeledError::new(
+            "The selected element is not visible within the viewport",
+        ));
+    }
+    let row = width as usize * 4;
+    let mut out = Vec::with_capacity((x1 - x0) * (y1 - y0) * 4);
+    for line in y0..y1 {
+        out.extend_from_slice(&buffer[line * row + x0 * 4..line * row + x1 * 4]);
+    }
+    Ok((out, (x1 - x0) as u32, (y1 - y0) as u32))
+}
+
+fn encode_png(buffer: &[u8], width: u32, height: u32) -> Result<Vec<u8>, LabeledError> {
+    let mut out: Vec<u8> = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(buffer))
+        .map_err(|err| LabeledError::new(format!("Failed to encode png: {err}")))?;
+    Ok(out)
+}
+
+/// Only serves `data:` urls - everything else is treated as unavailable,
+/// since rendering is not supposed to touch the network or filesystem.
+#[derive(Default)]
+struct DataUrlNetProvider {
+    resources: Arc<Mutex<Vec<Resource>>>,
+}
+
+impl DataUrlNetProvider {
+    fn take_resources(&self) -> Vec<Resource> {
+        std::mem::take(&mut *self.resources.lock().unwrap())
+    }
+}
+
+impl NetProvider<Resource> for DataUrlNetProvider {
+    fn fetch(&self, doc_id: usize, request: Request, handler: BoxedHandler<Resource>) {
+        if request.url.scheme() != "data" {
+            return;
+        }
+        let Ok(url) = data_url::DataUrl::process(request.url.as_str()) else {
+            return;
+        };
+        let Ok((body, _fragment)) = url.decode_to_vec() else {
+            return;
+        };
+        handler.bytes(
+            doc_id,
+            body.into(),
+            Arc::new(CollectingCallback(self.resources.clone())),
+        );
+    }
+}
+
+struct CollectingCallback(Arc<Mutex<Vec<Resource>>>);
+
+impl NetCallback<Resource> for CollectingCallback {
+    fn call(&self, _doc_id: usize, result: Result<Resource, Option<String>>) {
+        if let Ok(resource) = result {
+            self.0.lock().unwrap().push(resource);
+        }
+    }
+}
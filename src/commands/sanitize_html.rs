@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use cssparser::{ParseError, Parser, ParserInput, Token};
+use ego_tree::NodeId;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, SyntaxShape, Type, Value};
+use scraper::{Html, Node};
+
+use crate::HtmlBackendMut;
+use crate::css::stylesheet::parse_declaration_list;
+use crate::css::urls::find_urls;
+
+pub struct SanitizeHtmlCommand;
+
+impl SimplePluginCommand for SanitizeHtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html sanitize"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::String),
+                (Type::Binary, Type::String),
+            ])
+            .named(
+                "config",
+                SyntaxShape::Record(vec![]),
+                "allowlist, overrides the keys of the default preset (see description)",
+                Some('c'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            remove everything which is not explicitly allowed from an html fragment (such as user comments).
+            the config record supports the following keys, missing keys are taken from the default preset:
+              tags: list<string>                  elements to keep, others are unwrapped (their content is kept)
+              remove: list<string>                elements which are removed together with their content
+              attributes: record<tag: list<string>>  allowed attributes per tag ("*" for all tags)
+              url_schemes: list<string>           allowed schemes of urls (href, src, ...), relative urls are always allowed
+              css_properties: list<string>        allowed properties in style="" (which must be an allowed attribute, it is dropped if empty)
+            the default preset allows basic formatting (as produced by markdown), links and images.
+            on* event handlers, javascript: urls and comments are always removed.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let mut allowlist = Allowlist::default();
+        if let Some(config) = call.get_flag::<Value>("config")? {
+            allowlist.update(&config)?;
+        }
+
+        let mut html = crate::ScraperBackend.parse_fragment(input)?;
+        let root = html.root_element().id();
+        allowlist.sanitize_children(&mut html, root)?;
+        Ok(Value::string(html.root_element().inner_html(), call.head))
+    }
+}
+
+struct Allowlist {
+    tags: Vec<String>,
+    remove: Vec<String>,
+    /// tag -> attributes (`*` applies to all tags)
+    attributes: HashMap<String, Vec<String>>,
+    url_schemes: Vec<String>,
+    css_properties: Vec<String>,
+}
+
+fn strings(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| (*s).to_owned()).collect()
+}
+
+impl Default for Allowlist {
+    /// Safe for user comments and the output of markdown renderers.
+    fn default() -> Self {
+        Self {
+            tags: strings(&[
+                "a",
+                "abbr",
+                "b",
+                "blockquote",
+                "br",
+                "code",
+                "dd",
+                "del",
+                "details",
+                "div",
+                "dl",
+                "dt",
+                "em",
+                "h1",
+                "h2",
+                "h3",
+                "h4",
+                "h5",
+                "h6",
+                "hr",
+                "i",
+                "img",
+                "ins",
+                "kbd",
+                "li",
+                "mark",
+                "ol",
+                "p",
+                "pre",
+                "q",
+                "s",
+                "samp",
+                "small",
+                "span",
+                "strike",
+                "strong",
+                "sub",
+                "summary",
+                "sup",
+                "table",
+                "tbody",
+                "td",
+                "tfoot",
+                "th",
+                "thead",
+                "tr",
+                "u",
+                "ul",
+                "var",
+            ]),
+            remove: strings(&[
+                "script", "style", "template", "iframe", "frame", "frameset", "object", "embed",
+                "applet", "noscript", "noembed", "noframes", "title", "textarea", "select", "xmp",
+                "math", "svg",
+            ]),
+            attributes: HashMap::from([
+                (String::from("*"), strings(&["title", "lang", "dir"])),
+                (String::from("a"), strings(&["href"])),
+                (String::from("blockquote"), strings(&["cite"])),
+                (String::from("code"), strings(&["class"])),
+                (
+                    String::from("img"),
+                    strings(&["src", "alt", "width", "height"]),
+                ),
+                (String::from("li"), strings(&["value"])),
+                (String::from("ol"), strings(&["start", "type", "reversed"])),
+                (String::from("q"), strings(&["cite"])),
+                (
+                    String::from("td"),
+                    strings(&["colspan", "rowspan", "align"]),
+                ),
+                (
+                    String::from("th"),
+                    strings(&["colspan", "rowspan", "align"]),
+                ),
+            ]),
+            url_schemes: strings(&["http", "https", "mailto"]),
+            css_properties: Vec::new(),
+        }
+    }
+}
+
+fn config_strings(value: &Value, key: &str) -> Result<Vec<String>, LabeledError> {
+    let err = || {
+        LabeledError::new(format!("Invalid config value for '{key}'"))
+            .with_label("expected a list of strings", value.span())
+    };
+    match value {
+        Value::List { vals, .. } => vals
+            .iter()
+            .map(|v| match v {
+                Value::String { val, .. } => Ok(val.to_ascii_lowercase()),
+                _ => Err(err()),
+            })
+            .collect(),
+        _ => Err(err()),
+    }
+}
+
+impl Allowlist {
+    fn update(&mut self, config: &Value) -> Result<(), LabeledError> {
+        let config: &Record = match config {
+            Value::Record { val, .. } => val,
+            _ => return Err(LabeledError::new("Invalid config (expected record)")),
+        };
+        for (key, value) in config.iter() {
+            match key.as_str() {
+                "tags" => self.tags = config_strings(value, key)?,
+                "remove" => self.remove = config_strings(value, key)?,
+                "url_schemes" => self.url_schemes = config_strings(value, key)?,
+                "css_properties" => self.css_properties = config_strings(value, key)?,
+                "attributes" => {
+                    let Value::Record { val, .. } = value else {
+                        return Err(LabeledError::new("Invalid config value for 'attributes'")
+                            .with_label("expected record<tag: list<string>>", value.span()));
+                    };
+                    self.attributes = val
+                        .iter()
+                        .map(|(tag, attrs)| {
+                            Ok((tag.to_ascii_lowercase(), config_strings(attrs, key)?))
+                        })
+                        .collect::<Result<_, LabeledError>>()?;
+                }
+                _ => {
+                    return Err(LabeledError::new(format!("Unknown config key '{key}'"))
+                        .with_label(
+                            "expected one of tags, remove, attributes, url_schemes, css_properties",
+                            value.span(),
+                        ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sanitize_children(&self, html: &mut Html, parent: NodeId) -> Result<(), LabeledError> {
+        let children: Vec<NodeId> = html
+            .tree
+            .get(parent)
+            .expect("node ids come from the tree")
+            .children()
+            .map(|c| c.id())
+            .collect();
+        for child in children {
+            let mut node = html
+                .tree
+                .get_mut(child)
+                .expect("node ids come from the tree");
+            let tag = match node.value() {
+                Node::Text(_) => continue,
+                Node::Element(element) => element.name.local.to_string().to_ascii_lowercase(),
+                // comments, processing instructions, ...
+                _ => {
+                    node.detach();
+                    continue;
+                }
+            };
+            if self.remove.contains(&tag) {
+                node.detach();
+                continue;
+            }
+            self.sanitize_children(html, child)?;
+            if self.tags.contains(&tag) {
+                self.sanitize_attributes(html, child, &tag);
+            } else {
+                crate::ScraperBackend.unwrap(html, child)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn sanitize_attributes(&self, html: &mut Html, node_id: NodeId, tag: &str) {
+        let mut node = html
+            .tree
+            .get_mut(node_id)
+            .expect("node ids come from the tree");
+        let Node::Element(element) = node.value() else {
+            return;
+        };
+        let allowed: Vec<&String> = self
+            .attributes
+            .get("*")
+            .into_iter()
+            .chain(self.attributes.get(tag))
+            .flatten()
+            .collect();
+        element.attrs.retain_mut(|(name, value)| {
+            let name = name.local.to_string().to_ascii_lowercase();
+            if name.starts_with("on") {
+                return false;
+            }
+            if !allowed.contains(&&name) {
+                return false;
+            }
+            if name == "style" {
+                return match self.sanitize_style(value) {
+                    Some(style) => {
+                        *value = style.into();
+                        true
+                    }
+                    None => false,
+                };
+            }
+            match name.as_str() {
+                "srcset" => value
+                    .split(',')
+                    .filter_map(|candidate| candidate.split_whitespace().next())
+                    .all(|url| self.is_allowed_url(url)),
+                "href" | "src" | "cite" | "action" | "formaction" | "poster" | "background"
+                | "longdesc" | "data" | "ping" => self.is_allowed_url(value),
+                _ => true,
+            }
+        });
+    }
+
+    /// Returns the allowed declarations of a `style=""` attribute (`None` if there are none).
+    fn sanitize_style(&self, style: &str) -> Option<String> {
+        let declarations: Vec<String> = parse_declaration_list(style)
+            .0
+            .into_iter()
+            .filter(|d| self.css_properties.contains(&d.property))
+            // urls could load external resources, expression() is scripting in old IEs
+            .filter(|d| !loads_resources(&d.value))
+            .map(|d| d.to_css())
+            .collect();
+        if declarations.is_empty() {
+            None
+        } else {
+            Some(declarations.join("; "))
+        }
+    }
+
+    fn is_allowed_url(&self, url: &str) -> bool {
+        // browsers ignore whitespace and control characters in urls (`java\tscript:`)
+        let url: String = url
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+            .collect();
+        match url_scheme(&url) {
+            Some(scheme) => {
+                let scheme = scheme.to_ascii_lowercase();
+                scheme != "javascript" && scheme != "vbscript" && self.url_schemes.contains(&scheme)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Returns the scheme of absolute urls (`None` for relative urls).
+pub(crate) fn url_scheme(url: &str) -> Option<&str> {
+    let end = url.find([':', '/', '?', '#'])?;
+    let scheme = &url[..end];
+    let is_scheme = url[end..].starts_with(':')
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    is_scheme.then_some(scheme)
+}
+
+/// Whether a css value contains a url or a function which could load or run something.
+/// Works on tokens so escapes (`\75 rl(...)`) can't hide them.
+fn loads_resources(css: &str) -> bool {
+    let mut found = false;
+    find_urls(css, &mut |_, _| found = true);
+    found || has_resource_token(&mut Parser::new(&mut ParserInput::new(css)))
+}
+
+fn has_resource_token(input: &mut Parser<'_, '_>) -> bool {
+    while let Ok(token) = input.next() {
+        match token {
+            Token::UnquotedUrl(_) => return true,
+            Token::Function(name)
+                if ["url", "image-set", "-webkit-image-set", "expression"]
+                    .iter()
+                    .any(|f| name.eq_ignore_ascii_case(f)) =>
+            {
+                return true;
+            }
+            Token::Function(_)
+            | Token::ParenthesisBlock
+            | Token::SquareBracketBlock
+            | Token::CurlyBracketBlock => {
+                let nested = input
+                    .parse_nested_block(|i| Ok::<_, ParseError<'_, ()>>(has_resource_token(i)));
+                if nested.unwrap_or(false) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, SyntaxShape, Type, Value};
+
+use super::mime::{mime_record_type, mime_to_record};
+use crate::sniff::{SniffContext, SuppliedType, sniff};
+
+pub struct SniffMimeCommand;
+
+impl SimplePluginCommand for SniffMimeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime sniff"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::Binary, mime_record_type()),
+                (Type::String, mime_record_type()),
+            ])
+            .named(
+                "supplied",
+                SyntaxShape::String,
+                "the Content-Type the resource was served with",
+                Some('s'),
+            )
+            .switch(
+                "no-sniff",
+                "the resource was served with X-Content-Type-Options: nosniff",
+                Some('n'),
+            )
+            .named(
+                "context",
+                SyntaxShape::String,
+                "where the resource is used: browsing (default), image, audio-video, font, plugin, style, script, text-track or cache-manifest",
+                Some('c'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            determine the mime type of a resource like browsers do (WHATWG MIME sniffing standard).
+            the first 1445 bytes are matched against the signatures of images, audio/video, fonts, archives, html, xml and pdf.
+            with --supplied, the served Content-Type is only replaced where browsers would do it
+            (e.g. image/* with a different image signature or text/plain for binary content).
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let span = call.head;
+        let bytes: &[u8] = match input {
+            Value::Binary { val, .. } => val,
+            Value::String { val, .. } => val.as_bytes(),
+            _ => {
+                return Err(
+                    LabeledError::new("Invalid input (expected string or binary)")
+                        .with_label("here", input.span()),
+                );
+            }
+        };
+
+        let context = match call.get_flag_value("context") {
+            None => SniffContext::Browsing,
+            Some(value) => {
+                let name = value.as_str()?;
+                SniffContext::parse(name).ok_or_else(|| {
+                    LabeledError::new(format!("Unknown context '{name}'"))
+                        .with_label("here", value.span())
+                        .with_help(format!("use one of {}", SniffContext::NAMES.join(", ")))
+                })?
+            }
+        };
+        let supplied: Option<String> = call.get_flag("supplied")?;
+        // an invalid Content-Type is treated like a missing one
+        let supplied = supplied
+            .as_deref()
+            .filter(|s| mime::Mime::from_str(s).is_ok())
+            .map(SuppliedType::new);
+
+        let computed = sniff(
+            bytes,
+            supplied.as_ref(),
+            call.has_flag("no-sniff")?,
+            context,
+        );
+        let mime = mime::Mime::from_str(&computed).map_err(|err| {
+            LabeledError::new(format!("Failed to parse mime: {err}")).with_label("here", span)
+        })?;
+        Ok(mime_to_record(&mime, span))
+    }
+}
@@ -0,0 +1,168 @@
+use std::str::FromStr;
+
+use data_url::mime::Mime;
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, SyntaxShape, Type, Value};
+
+use crate::codec::{base64_encode, percent_encode};
+
+pub struct ToDataUrlCommand;
+
+impl SimplePluginCommand for ToDataUrlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo data-url to"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::String),
+                (Type::Binary, Type::String),
+            ])
+            .optional(
+                "mime",
+                SyntaxShape::OneOf(vec![SyntaxShape::Record(vec![]), SyntaxShape::String]),
+                "mime type as string or {type, subtype, parameters} record (default: text/plain;charset=utf-8 for strings, application/octet-stream for binary)",
+            )
+            .switch("base64", "always use base64", Some('b'))
+            .switch("percent", "always use percent-encoding", Some('p'))
+            .named(
+                "fragment",
+                SyntaxShape::String,
+                "append a fragment (#...)",
+                None,
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            encode the input into a data: url. strings are encoded as utf-8.
+            by default the shorter one of base64 and percent-encoding is used.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let (body, default_mime): (&[u8], &str) = match input {
+            Value::String { val, .. } => (val.as_bytes(), "text/plain;charset=utf-8"),
+            Value::Binary { val, .. } => (val, "application/octet-stream"),
+            _ => {
+                return Err(LabeledError::new(
+                    "Invalid input (expected string or binary)",
+                ));
+            }
+        };
+        let mime = match call.opt::<Value>(0)? {
+            Some(value) => mime_from_value(&value)?,
+            None => Mime::from_str(default_mime).expect("the default mime types are valid"),
+        };
+        let encoding = match (call.has_flag("base64")?, call.has_flag("percent")?) {
+            (true, true) => {
+                return Err(
+                    LabeledError::new("'--base64' and '--percent' can't be combined")
+                        .with_label("use only one of them", call.head),
+                );
+            }
+            (true, false) => Some(Encoding::Base64),
+            (false, true) => Some(Encoding::Percent),
+            (false, false) => None,
+        };
+        let mut url = encode_data_url(&mime, body, encoding);
+        if let Some(fragment) = call.get_flag::<String>("fragment")? {
+            url.push('#');
+            url.push_str(&percent_encode(fragment.as_bytes(), |b| {
+                !b.is_ascii_graphic() || matches!(b, b'"' | b'<' | b'>' | b'`')
+            }));
+        }
+        Ok(Value::string(url, call.head))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) enum Encoding {
+    Base64,
+    Percent,
+}
+
+/// Accepts mime types as string or in the `{type, subtype, parameters}` format of `servo mime parse`.
+pub(crate) fn mime_from_value(value: &Value) -> Result<Mime, LabeledError> {
+    let invalid =
+        |text: String| LabeledError::new("Invalid mime type").with_label(text, value.span());
+    let string = match value {
+        Value::String { val, .. } => val.clone(),
+        Value::Record { val, .. } => {
+            let field = |key: &str| match val.get(key) {
+                Some(Value::String { val, .. }) => Ok(val.clone()),
+                Some(other) => Err(invalid(format!(
+                    "expected string for '{key}', got {}",
+                    other.get_type()
+                ))),
+                None => Err(invalid(format!("missing '{key}'"))),
+            };
+            let mut mime = Mime::new(&field("type")?, &field("subtype")?);
+            match val.get("parameters") {
+                None | Some(Value::Nothing { .. }) => {}
+                Some(Value::Record { val, .. }) => {
+                    for (k, v) in val.iter() {
+                        let v = v
+                            .coerce_string()
+                            .map_err(|_| invalid(format!("expected string for parameter '{k}'")))?;
+                        mime.parameters.push((k.to_ascii_lowercase(), v));
+                    }
+                }
+                Some(other) => {
+                    return Err(invalid(format!(
+                        "expected record for 'parameters', got {}",
+                        other.get_type()
+                    )));
+                }
+            }
+            // serializing and parsing again validates the parts
+            mime.to_string()
+        }
+        other => {
+            return Err(invalid(format!(
+                "expected string or record, got {}",
+                other.get_type()
+            )));
+        }
+    };
+    Mime::from_str(&string).map_err(|err| invalid(format!("{err}: {string}")))
+}
+
+/// Builds a `data:` url (`encoding: None` picks the shorter one).
+pub(crate) fn encode_data_url(mime: &Mime, body: &[u8], encoding: Option<Encoding>) -> String {
+    let percent = || percent_encode(body, |b| !is_data_url_safe(b));
+    let (encoded, encoding) = match encoding {
+        Some(Encoding::Base64) => (base64_encode(body), Encoding::Base64),
+        Some(Encoding::Percent) => (percent(), Encoding::Percent),
+        None => {
+            let percent = percent();
+            // `;base64` has to be added
+            if percent.len() <= body.len().div_ceil(3) * 4 + 7 {
+                (percent, Encoding::Percent)
+            } else {
+                (base64_encode(body), Encoding::Base64)
+            }
+        }
+    };
+    let mut url = format!("data:{mime}");
+    if encoding == Encoding::Base64 {
+        url.push_str(";base64");
+    }
+    url.push(',');
+    url.push_str(&encoded);
+    url
+}
+
+/// Bytes which can stay as they are (quotes are encoded to make embedding into html/css easy).
+fn is_data_url_safe(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~!$&()*+,;=:@/".contains(&b)
+}
@@ -0,0 +1,50 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Signature, Type, Value};
+
+use super::mime::MediaType;
+
+pub struct ToMimeCommand;
+
+impl SimplePluginCommand for ToMimeCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime to"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::record(), Type::String),
+                (Type::String, Type::String),
+            ])
+            .switch(
+                "essence",
+                "only type/subtype, without parameters",
+                Some('e'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            serialize a mime type in the format of 'servo mime parse' (or normalize a string).
+            type, subtype and parameter names are lowercased, parameter values are quoted when needed.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let mime = MediaType::from_value(input)?;
+        let out = if call.has_flag("essence")? {
+            mime.essence()
+        } else {
+            mime.to_string()
+        };
+        Ok(Value::string(out, call.head))
+    }
+}
@@ -0,0 +1,158 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, SyntaxShape, Type, Value};
+
+use super::mime::MediaType;
+use super::parse_multipart::boundary;
+use crate::multipart::{Part, header, quote_disposition_value, serialize_multipart};
+
+pub struct ToMultipartCommand;
+
+impl SimplePluginCommand for ToMultipartCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo mime multipart to"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_type(Type::table(), Type::Binary)
+            .required(
+                "boundary",
+                SyntaxShape::String,
+                "the boundary or a Content-Type with a boundary parameter",
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            build a multipart body from parts in the format of 'servo mime multipart parse'.
+            headers (table of name/value or record) are written as they are, Content-Disposition and Content-Type
+            are added from disposition (default form-data), name, filename and content_type if the headers don't have them.
+            body can be binary or string. parts of nested multiparts are serialized with the boundary of their content type
+            (and replace their body).
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let boundary_value: Value = call.req(0)?;
+        let boundary = boundary(&boundary_value)?;
+        let body = serialize_parts(input, &boundary)?;
+        Ok(Value::binary(body, call.head))
+    }
+}
+
+fn serialize_parts(input: &Value, boundary: &str) -> Result<Vec<u8>, LabeledError> {
+    let parts = input
+        .as_list()?
+        .iter()
+        .map(value_to_part)
+        .collect::<Result<Vec<_>, _>>()?;
+    serialize_multipart(&parts, boundary).map_err(|err| {
+        LabeledError::new("Failed to build multipart body").with_label(err, input.span())
+    })
+}
+
+fn value_to_part(value: &Value) -> Result<Part, LabeledError> {
+    let record = value.as_record()?;
+    let string = |key: &str| -> Result<Option<String>, LabeledError> {
+        match record.get(key) {
+            None | Some(Value::Nothing { .. }) => Ok(None),
+            Some(v) => Ok(Some(v.as_str()?.to_owned())),
+        }
+    };
+
+    let mut headers = match record.get("headers") {
+        None | Some(Value::Nothing { .. }) => Vec::new(),
+        Some(Value::Record { val, .. }) => val
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), value.coerce_string()?)))
+            .collect::<Result<Vec<_>, LabeledError>>()?,
+        Some(Value::List { vals, .. }) => vals
+            .iter()
+            .map(|row| {
+                let row: &Record = row.as_record()?;
+                let get = |key: &str| -> Result<String, LabeledError> {
+                    match row.get(key) {
+                        Some(v) => Ok(v.coerce_string()?),
+                        None => Err(LabeledError::new(format!("Missing '{key}'"))
+                            .with_label("headers need a name and a value", value.span())),
+                    }
+                };
+                Ok((get("name")?, get("value")?))
+            })
+            .collect::<Result<Vec<_>, LabeledError>>()?,
+        Some(v) => {
+            return Err(LabeledError::new("Invalid value for 'headers'").with_label(
+                format!("expected table or record, got {}", v.get_type()),
+                v.span(),
+            ));
+        }
+    };
+    for (name, header_value) in &headers {
+        if name.contains([':', '\r', '\n']) || header_value.contains(['\r', '\n']) {
+            return Err(
+                LabeledError::new(format!("Invalid header '{name}'")).with_label(
+                    "header names and values can't contain line breaks",
+                    value.span(),
+                ),
+            );
+        }
+    }
+
+    if header(&headers, "content-disposition").is_none() {
+        let name = string("name")?;
+        let filename = string("filename")?;
+        let kind = string("disposition")?;
+        if name.is_some() || filename.is_some() || kind.is_some() {
+            let mut disposition = kind.unwrap_or_else(|| String::from("form-data"));
+            if let Some(name) = name {
+                disposition.push_str("; name=");
+                disposition.push_str(&quote_disposition_value(&name));
+            }
+            if let Some(filename) = filename {
+                disposition.push_str("; filename=");
+                disposition.push_str(&quote_disposition_value(&filename));
+            }
+            headers.push((String::from("Content-Disposition"), disposition));
+        }
+    }
+    if header(&headers, "content-type").is_none() {
+        match record.get("content_type") {
+            None | Some(Value::Nothing { .. }) => {}
+            Some(v) => headers.push((
+                String::from("Content-Type"),
+                MediaType::from_value(v)?.to_string(),
+            )),
+        }
+    }
+
+    let body = match record.get("parts") {
+        Some(parts @ Value::List { .. }) => {
+            let content_type = header(&headers, "content-type").ok_or_else(|| {
+                LabeledError::new("Missing Content-Type")
+                    .with_label("nested parts need a multipart content type", parts.span())
+            })?;
+            let boundary = boundary(&Value::string(content_type, parts.span()))?;
+            serialize_parts(parts, &boundary)?
+        }
+        _ => match record.get("body") {
+            None | Some(Value::Nothing { .. }) => Vec::new(),
+            Some(Value::Binary { val, .. }) => val.clone(),
+            Some(Value::String { val, .. }) => val.as_bytes().to_vec(),
+            Some(v) => {
+                return Err(LabeledError::new("Invalid value for 'body'").with_label(
+                    format!("expected binary or string, got {}", v.get_type()),
+                    v.span(),
+                ));
+            }
+        },
+    };
+    Ok(Part { headers, body })
+}
@@ -0,0 +1,171 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Type, Value};
+use url::{Url, form_urlencoded};
+
+pub struct ToUrlCommand;
+
+impl SimplePluginCommand for ToUrlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo url to"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name()).input_output_type(Type::record(), Type::String)
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            build a url from a record in the format of 'servo url parse' (only scheme is required).
+            href, host_type and origin are ignored. path segments are expected to be percent-encoded.
+            params can also be a record<key: value>. if query and params are both given, params win (so params can be updated in a parsed url).
+            the result is normalized like any parsed url.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let Value::Record { val: record, .. } = input else {
+            return Err(LabeledError::new("Invalid input (expected record)"));
+        };
+        let Some(scheme) = optional_string(record, "scheme")? else {
+            return Err(LabeledError::new("Missing 'scheme'")
+                .with_label("the url needs a scheme", input.span()));
+        };
+
+        let mut out = format!("{scheme}:");
+        let host = optional_string(record, "host")?;
+        let special = matches!(
+            scheme.to_ascii_lowercase().as_str(),
+            "http" | "https" | "ws" | "wss" | "ftp" | "file"
+        );
+        if host.is_some() || special {
+            out.push_str("//");
+            let username = optional_string(record, "username")?.unwrap_or_default();
+            let password = optional_string(record, "password")?;
+            if !username.is_empty() || password.is_some() {
+                out.push_str(&username);
+                if let Some(password) = password {
+                    out.push(':');
+                    out.push_str(&password);
+                }
+                out.push('@');
+            }
+            out.push_str(&host.unwrap_or_default());
+            match record.get("port") {
+                None | Some(Value::Nothing { .. }) => {}
+                Some(Value::Int { val, .. }) => out.push_str(&format!(":{val}")),
+                Some(value) => return Err(invalid_value("port", "int", value)),
+            }
+        }
+        match record.get("path") {
+            None | Some(Value::Nothing { .. }) => {}
+            Some(Value::String { val, .. }) => out.push_str(val),
+            Some(Value::List { vals, .. }) => {
+                for segment in vals {
+                    let Value::String { val, .. } = segment else {
+                        return Err(invalid_value("path", "list<string>", segment));
+                    };
+                    out.push('/');
+                    out.push_str(val);
+                }
+            }
+            Some(value) => return Err(invalid_value("path", "list<string> or string", value)),
+        }
+        if let Some(query) = query(record)? {
+            out.push('?');
+            out.push_str(&query);
+        }
+        if let Some(fragment) = optional_string(record, "fragment")? {
+            out.push('#');
+            out.push_str(&fragment);
+        }
+
+        let url = Url::parse(&out).map_err(|err| {
+            LabeledError::new("Failed to build url")
+                .with_label(format!("{err} (in \"{out}\")"), input.span())
+        })?;
+        Ok(Value::string(url.as_str(), call.head))
+    }
+}
+
+fn invalid_value(key: &str, expected: &str, value: &Value) -> LabeledError {
+    LabeledError::new(format!("Invalid value for '{key}'")).with_label(
+        format!("expected {expected}, got {}", value.get_type()),
+        value.span(),
+    )
+}
+
+fn optional_string(record: &Record, key: &str) -> Result<Option<String>, LabeledError> {
+    match record.get(key) {
+        None | Some(Value::Nothing { .. }) => Ok(None),
+        Some(Value::String { val, .. }) => Ok(Some(val.clone())),
+        Some(value) => Err(invalid_value(key, "string", value)),
+    }
+}
+
+/// The query from `query` or (encoded) `params`.
+fn query(record: &Record) -> Result<Option<String>, LabeledError> {
+    let query = optional_string(record, "query")?;
+    let params: Option<Vec<(String, String)>> = match record.get("params") {
+        None | Some(Value::Nothing { .. }) => None,
+        Some(Value::Record { val, .. }) => Some(
+            val.iter()
+                .map(|(k, v)| Ok((k.clone(), param_value(v)?)))
+                .collect::<Result<_, LabeledError>>()?,
+        ),
+        Some(Value::List { vals, .. }) => Some(
+            vals.iter()
+                .map(|pair| {
+                    let Value::Record { val, .. } = pair else {
+                        return Err(invalid_value("params", "record<key, value>", pair));
+                    };
+                    let key = optional_string(val, "key")?.unwrap_or_default();
+                    let value = match val.get("value") {
+                        Some(v) => param_value(v)?,
+                        None => String::new(),
+                    };
+                    Ok((key, value))
+                })
+                .collect::<Result<_, LabeledError>>()?,
+        ),
+        Some(value) => {
+            return Err(invalid_value(
+                "params",
+                "list<record<key, value>> or record",
+                value,
+            ));
+        }
+    };
+    let Some(params) = params else {
+        return Ok(query);
+    };
+    // params win, the query is only kept (with its original encoding) if it still matches them
+    if let Some(query) = query
+        && form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .eq(params.iter().cloned())
+    {
+        return Ok(Some(query));
+    }
+    if params.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .finish(),
+    ))
+}
+
+fn param_value(value: &Value) -> Result<String, LabeledError> {
+    value
+        .coerce_string()
+        .map_err(|_| invalid_value("params", "string values", value))
+}
@@ -0,0 +1,327 @@
+use nu_plugin::SimplePluginCommand;
+use nu_protocol::{LabeledError, Record, Signature, Span, SyntaxShape, Type, Value};
+
+use crate::{HtmlBackend, HtmlBackendMut, InsertPosition};
+
+pub struct UpdateHtmlCommand;
+
+/// The flags (and keys of operation records) in the order they are applied.
+const OPERATIONS: [&str; 11] = [
+    "set-attribute",
+    "remove-attribute",
+    "add-class",
+    "remove-class",
+    "inner-html",
+    "text",
+    "before",
+    "after",
+    "wrap",
+    "unwrap",
+    "remove",
+];
+
+impl SimplePluginCommand for UpdateHtmlCommand {
+    type Plugin = crate::plugin_interface::NuPluginServo;
+
+    fn name(&self) -> &str {
+        "servo html update"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(self.name())
+            .input_output_types(vec![
+                (Type::String, Type::String),
+                (Type::Binary, Type::String),
+            ])
+            .required(
+                "selector",
+                SyntaxShape::OneOf(vec![
+                    SyntaxShape::List(Box::new(SyntaxShape::Record(vec![]))),
+                    SyntaxShape::String,
+                ]),
+                "css selector of the elements to update, or a list of {selector, <operation>: <argument>} records",
+            )
+            .named(
+                "set-attribute",
+                SyntaxShape::Record(vec![]),
+                "set attributes (record<name: value>)",
+                Some('s'),
+            )
+            .named(
+                "remove-attribute",
+                SyntaxShape::List(Box::new(SyntaxShape::String)),
+                "remove attributes",
+                Some('r'),
+            )
+            .named(
+                "add-class",
+                SyntaxShape::List(Box::new(SyntaxShape::String)),
+                "add classes",
+                None,
+            )
+            .named(
+                "remove-class",
+                SyntaxShape::List(Box::new(SyntaxShape::String)),
+                "remove classes",
+                None,
+            )
+            .named(
+                "inner-html",
+                SyntaxShape::String,
+                "replace the content with an html fragment",
+                None,
+            )
+            .named("text", SyntaxShape::String, "replace the content with text", None)
+            .named(
+                "before",
+                SyntaxShape::String,
+                "insert an html fragment before the element",
+                None,
+            )
+            .named(
+                "after",
+                SyntaxShape::String,
+                "insert an html fragment after the element",
+                None,
+            )
+            .named(
+                "wrap",
+                SyntaxShape::String,
+                "wrap the element (into the innermost first element of the fragment)",
+                None,
+            )
+            .switch("unwrap", "replace the element with its content", None)
+            .switch("remove", "remove the element", None)
+            .switch(
+                "fragment",
+                "the input is an html fragment (don't add <html>, <head> and <body>)",
+                Some('f'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        r#"
+            modify the elements matching a css selector and return the updated html.
+            pass a list of records (such as [{selector: "img", set-attribute: {loading: lazy}}, {selector: "script", remove: true}])
+            to apply multiple operations at once, they are applied in order.
+            within an operation the steps are applied in the order of the flags above.
+        "#
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        _engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: &Value,
+    ) -> Result<Value, LabeledError> {
+        let operations: Vec<Operation> = match call.req::<Value>(0)? {
+            Value::List { vals, .. } => {
+                for flag in OPERATIONS {
+                    // `has_flag` fails for flags which take a value
+                    if call.get_flag_value(flag).is_none() && !call.has_flag(flag)? {
+                        continue;
+                    }
+                    return Err(LabeledError::new(format!(
+                        "'--{flag}' can't be combined with a list of operations"
+                    ))
+                    .with_label("put it into the operation records", call.head));
+                }
+                vals.iter()
+                    .map(Operation::from_record)
+                    .collect::<Result<_, _>>()?
+            }
+            selector => {
+                let mut record = Record::new();
+                record.push("selector", selector);
+                for flag in OPERATIONS {
+                    match call.get_flag_value(flag) {
+                        Some(value) => record.push(flag, value),
+                        // switches don't have a value
+                        None if call.has_flag(flag)? => {
+                            record.push(flag, Value::bool(true, call.head))
+                        }
+                        None => {}
+                    }
+                }
+                vec![Operation::from_record(&Value::record(record, call.head))?]
+            }
+        };
+
+        let b = crate::ScraperBackend;
+        let fragment = call.has_flag("fragment")?;
+        let mut html = if fragment {
+            b.parse_fragment(input)?
+        } else {
+            b.parse_with_doctype(input)?
+        };
+
+        for operation in operations.iter() {
+            operation.apply(&b, &mut html)?;
+        }
+
+        let out = if fragment {
+            b.inner_html(&html, b.get_root_node(&html)?)?
+        } else {
+            b.document_html(&html)?
+        };
+        Ok(Value::string(out, call.head))
+    }
+}
+
+#[derive(Default)]
+struct Operation {
+    selector: String,
+    selector_span: Option<Span>,
+    set_attributes: Vec<(String, String)>,
+    remove_attributes: Vec<String>,
+    add_classes: Vec<String>,
+    remove_classes: Vec<String>,
+    inner_html: Option<String>,
+    text: Option<String>,
+    before: Option<String>,
+    after: Option<String>,
+    wrap: Option<String>,
+    unwrap: bool,
+    remove: bool,
+}
+
+fn expect_strings(value: &Value) -> Result<Vec<String>, LabeledError> {
+    match value {
+        Value::List { vals, .. } => vals.iter().map(expect_string).collect(),
+        // a single string is fine too
+        _ => Ok(vec![expect_string(value)?]),
+    }
+}
+
+fn expect_string(value: &Value) -> Result<String, LabeledError> {
+    value.coerce_string().map_err(|_| {
+        LabeledError::new("Invalid operation argument").with_label(
+            format!("expected string, got {}", value.get_type()),
+            value.span(),
+        )
+    })
+}
+
+fn expect_bool(value: &Value) -> Result<bool, LabeledError> {
+    value.as_bool().map_err(|_| {
+        LabeledError::new("Invalid operation argument").with_label(
+            format!("expected bool, got {}", value.get_type()),
+            value.span(),
+        )
+    })
+}
+
+impl Operation {
+    fn from_record(value: &Value) -> Result<Self, LabeledError> {
+        let Value::Record { val: record, .. } = value else {
+            return Err(LabeledError::new("Invalid operation")
+                .with_label("expected record<selector: string, ..>", value.span()));
+        };
+        let mut operation = Operation::default();
+        for (key, value) in record.iter() {
+            match key.as_str() {
+                "selector" => {
+                    operation.selector = expect_string(value)?;
+                    operation.selector_span = Some(value.span());
+                }
+                "set-attribute" => {
+                    let Value::Record { val, .. } = value else {
+                        return Err(LabeledError::new("Invalid operation argument")
+                            .with_label("expected record<name: value>", value.span()));
+                    };
+                    for (name, value) in val.iter() {
+                        operation
+                            .set_attributes
+                            .push((name.clone(), expect_string(value)?));
+                    }
+                }
+                "remove-attribute" => operation.remove_attributes = expect_strings(value)?,
+                "add-class" => operation.add_classes = expect_strings(value)?,
+                "remove-class" => operation.remove_classes = expect_strings(value)?,
+                "inner-html" => operation.inner_html = Some(expect_string(value)?),
+                "text" => operation.text = Some(expect_string(value)?),
+                "before" => operation.before = Some(expect_string(value)?),
+                "after" => operation.after = Some(expect_string(value)?),
+                "wrap" => operation.wrap = Some(expect_string(value)?),
+                "unwrap" => operation.unwrap = expect_bool(value)?,
+                "remove" => operation.remove = expect_bool(value)?,
+                _ => {
+                    return Err(LabeledError::new(format!("Unknown operation '{key}'"))
+                        .with_label(
+                            format!("expected selector or one of {}", OPERATIONS.join(", ")),
+                            value.span(),
+                        ));
+                }
+            }
+        }
+        if operation.selector_span.is_none() {
+            return Err(LabeledError::new("Operation without selector")
+                .with_label("missing 'selector'", value.span()));
+        }
+        Ok(operation)
+    }
+
+    fn apply<B: HtmlBackendMut>(&self, b: &B, html: &mut B::Document) -> Result<(), LabeledError> {
+        let nodes: Vec<B::NodeId> = b
+            .css_query(html, &self.selector)
+            .map_err(|err| match self.selector_span {
+                Some(span) => err.with_label("invalid selector", span),
+                None => err,
+            })?
+            .into_iter()
+            .map(|node| b.node_id(node))
+            // the tree keeps the nodes removed by previous operations (and the parsing
+            // context of fragments), queries visit them too
+            .filter(|node| b.is_attached(html, *node))
+            .collect();
+
+        for node in nodes {
+            for (name, value) in self.set_attributes.iter() {
+                b.set_attribute(html, node, name, value)?;
+            }
+            for name in self.remove_attributes.iter() {
+                b.remove_attribute(html, node, name)?;
+            }
+            if !self.add_classes.is_empty() || !self.remove_classes.is_empty() {
+                let current = b.get_attribute(html, node, "class").unwrap_or_default();
+                let mut classes: Vec<&str> = current
+                    .split_ascii_whitespace()
+                    .filter(|c| !self.remove_classes.iter().any(|r| r == c))
+                    .collect();
+                for class in self.add_classes.iter() {
+                    if !classes.contains(&class.as_str()) {
+                        classes.push(class);
+                    }
+                }
+                if classes.is_empty() {
+                    b.remove_attribute(html, node, "class")?;
+                } else {
+                    b.set_attribute(html, node, "class", &classes.join(" "))?;
+                }
+            }
+            if let Some(fragment) = &self.inner_html {
+                b.set_inner_html(html, node, fragment)?;
+            }
+            if let Some(text) = &self.text {
+                b.set_text(html, node, text)?;
+            }
+            if let Some(fragment) = &self.before {
+                b.insert_html(html, node, InsertPosition::Before, fragment)?;
+            }
+            if let Some(fragment) = &self.after {
+                b.insert_html(html, node, InsertPosition::After, fragment)?;
+            }
+            if let Some(wrapper) = &self.wrap {
+                b.wrap(html, node, wrapper)?;
+            }
+            if self.unwrap {
+                b.unwrap(html, node)?;
+            }
+            if self.remove {
+                b.remove(html, node)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use cssparser::{ParseError, Parser, ParserInput, ToCss, Token, TokenSerializationType};
+
+/// Removes comments and whitespace which doesn't change the meaning of a stylesheet
+/// (or declaration list). Tokens are kept as they are.
+pub fn minify_css(css: &str) -> String {
+    let mut input = ParserInput::new(css);
+    let mut input = Parser::new(&mut input);
+    let mut minifier = Minifier {
+        out: String::new(),
+        previous: TokenSerializationType::Nothing,
+        pending_space: false,
+        statement: StatementState::Start,
+    };
+    minifier.tokens(&mut input);
+    minifier.out
+}
+
+/// Used to find the `:` of declarations (whitespace after it can be removed,
+/// unlike in selectors such as `a :hover`).
+#[derive(Copy, Clone, PartialEq)]
+enum StatementState {
+    /// after `{`, `;` or `}`
+    Start,
+    /// a single identifier
+    Ident,
+    /// `ident:`
+    Declaration,
+    Other,
+}
+
+struct Minifier {
+    out: String,
+    previous: TokenSerializationType,
+    pending_space: bool,
+    statement: StatementState,
+}
+
+impl Minifier {
+    fn tokens(&mut self, input: &mut Parser<'_, '_>) {
+        while let Ok(token) = input.next_including_whitespace_and_comments() {
+            let token = token.clone();
+            match token {
+                Token::WhiteSpace(_) | Token::Comment(_) => {
+                    self.pending_space = true;
+                    continue;
+                }
+                Token::Colon
+                    if self.statement == StatementState::Ident && is_declaration(input) =>
+                {
+                    // `color :red`
+                    self.pending_space = false;
+                }
+                _ => {}
+            }
+            self.write_separator(&token);
+            match token {
+                Token::Semicolon | Token::CurlyBracketBlock => {
+                    self.statement = StatementState::Start
+                }
+                Token::Ident(_) if self.statement == StatementState::Start => {
+                    self.statement = StatementState::Ident
+                }
+                Token::Colon if self.statement == StatementState::Ident => {
+                    self.statement = StatementState::Declaration
+                }
+                _ => self.statement = StatementState::Other,
+            }
+            // writing into a string can't fail
+            let _ = token.to_css(&mut self.out);
+            self.previous = token.serialization_type();
+
+            let closing = match token {
+                Token::Function(_) | Token::ParenthesisBlock => Some(")"),
+                Token::SquareBracketBlock => Some("]"),
+                Token::CurlyBracketBlock => Some("}"),
+                _ => None,
+            };
+            if let Some(closing) = closing {
+                let _ = input.parse_nested_block(|i| -> Result<(), ParseError<'_, ()>> {
+                    self.tokens(i);
+                    Ok(())
+                });
+                if closing == "}" {
+                    // the last declaration doesn't need a semicolon
+                    if self.out.ends_with(';') {
+                        self.out.pop();
+                    }
+                    self.statement = StatementState::Start;
+                }
+                self.out.push_str(closing);
+                self.pending_space = false;
+                self.previous = TokenSerializationType::Nothing;
+            }
+        }
+    }
+
+    /// Writes a space if there was whitespace before `next` and it is needed.
+    fn write_separator(&mut self, next: &Token<'_>) {
+        let pending_space = std::mem::take(&mut self.pending_space);
+        if self.out.is_empty() {
+            return;
+        }
+        if self
+            .previous
+            .needs_separator_when_before(next.serialization_type())
+        {
+            self.out.push(' ');
+            return;
+        }
+        if !pending_space || self.statement == StatementState::Declaration {
+            return;
+        }
+        let after_punctuation = self.out.ends_with(['{', '}', ';', ',', '>', '(', '[']);
+        let before_punctuation = matches!(
+            next,
+            Token::CurlyBracketBlock
+                | Token::CloseCurlyBracket
+                | Token::Semicolon
+                | Token::Comma
+                | Token::Delim('>')
+                | Token::CloseParenthesis
+                | Token::CloseSquareBracket
+        );
+        if !after_punctuation && !before_punctuation {
+            self.out.push(' ');
+        }
+    }
+}
+
+/// Whether the rest of the statement is the value of a declaration (it ends with `;` or
+/// the end of the block) and not a selector such as `a :hover {}`.
+fn is_declaration(input: &mut Parser<'_, '_>) -> bool {
+    let state = input.state();
+    let mut declaration = true;
+    while let Ok(token) = input.next() {
+        match token {
+            Token::Semicolon => break,
+            Token::CurlyBracketBlock => {
+                declaration = false;
+                break;
+            }
+            _ => {}
+        }
+    }
+    input.reset(&state);
+    declaration
+}
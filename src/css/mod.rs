@@ -0,0 +1,87 @@
+pub mod minify;
+pub mod properties;
+pub mod selector;
+pub mod stylesheet;
+pub mod urls;
+pub mod values;
+
+use cssparser::{ParseError, ParseErrorKind};
+use nu_protocol::{Record, Span, Value};
+
+/// A recoverable error found while parsing CSS (the parser skips the offending part).
+pub struct CssError {
+    pub message: String,
+    pub line: u32,
+    pub column: u32,
+    /// the skipped input
+    pub source: String,
+}
+
+impl CssError {
+    pub(crate) fn new(err: ParseError<'_, String>, source: &str) -> Self {
+        Self {
+            message: err.kind.to_string(),
+            line: err.location.line + 1,
+            column: err.location.column,
+            source: source.trim().to_owned(),
+        }
+    }
+
+    pub(crate) fn from_style_error(
+        err: ParseError<'_, style_traits::StyleParseErrorKind<'_>>,
+        source: &str,
+    ) -> Self {
+        Self {
+            message: match err.kind {
+                ParseErrorKind::Basic(kind) => kind.to_string(),
+                ParseErrorKind::Custom(kind) => format!("{kind:?}"),
+            },
+            line: err.location.line + 1,
+            column: err.location.column,
+            source: source.trim().to_owned(),
+        }
+    }
+
+    /// The part of `span` (the span of the parsed `css`) from the error's position to the end.
+    ///
+    /// Falls back to the whole span when it doesn't cover the css itself (string literals
+    /// include their quotes, values computed by a pipeline have no source text).
+    pub fn span_in(&self, css: &str, span: Span) -> Span {
+        let start = match span.len().checked_sub(css.len()) {
+            Some(0) => span.start,
+            Some(2) => span.start + 1,
+            _ => return span,
+        };
+        let Some(line) = css.split_inclusive('\n').nth(self.line as usize - 1) else {
+            return span;
+        };
+        let line_start = line.as_ptr() as usize - css.as_ptr() as usize;
+        // cssparser counts columns (from 1) in UTF-16 code units
+        let mut units = 1;
+        let mut column = line.len();
+        for (i, c) in line.char_indices() {
+            if units >= self.column {
+                column = i;
+                break;
+            }
+            units += c.len_utf16() as u32;
+        }
+        Span::new(start + line_start + column, start + css.len())
+    }
+
+    pub fn to_nu(&self, span: Span) -> Value {
+        let mut out = Record::new();
+        out.push("message", Value::string(&self.message, span));
+        out.push("line", Value::int(self.line as i64, span));
+        out.push("column", Value::int(self.column as i64, span));
+        out.push("source", Value::string(&self.source, span));
+        Value::record(out, span)
+    }
+}
+
+/// stylo wants to know where a stylesheet came from (to resolve `url()`s), but we don't know.
+pub(crate) fn dummy_url_data() -> style::stylesheets::UrlExtraData {
+    url::Url::parse("about:blank")
+        .expect("'about:blank' is a valid url")
+        .into()
+}
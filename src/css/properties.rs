@@ -0,0 +1,83 @@
+use style::context::QuirksMode;
+use style::properties::{
+    PropertyDeclaration, PropertyId, SourcePropertyDeclaration, parse_one_declaration_into,
+    parse_style_attribute,
+};
+use style::stylesheets::{CssRuleType, Origin};
+use style_traits::ParsingMode;
+
+use super::stylesheet::Declaration;
+
+/// Parses a declaration block with stylo.
+/// Shorthands are expanded into longhands and invalid declarations are dropped.
+pub fn parse_expanded_declarations(css: &str) -> Vec<Declaration> {
+    let url_data = super::dummy_url_data();
+    let block = parse_style_attribute(
+        css,
+        &url_data,
+        None,
+        QuirksMode::NoQuirks,
+        CssRuleType::Style,
+    );
+    block
+        .declaration_importance_iter()
+        .map(|(declaration, importance)| {
+            let mut declaration = to_declaration(declaration);
+            declaration.important = importance.important();
+            declaration
+        })
+        .collect()
+}
+
+pub enum PropertyValueError {
+    UnknownProperty,
+    InvalidValue,
+}
+
+/// Parses the value of a single property with stylo.
+/// Returns one declaration for longhands and the expanded longhands for shorthands.
+pub fn parse_property_value(
+    property: &str,
+    value: &str,
+) -> Result<Vec<Declaration>, PropertyValueError> {
+    let id = PropertyId::parse_enabled_for_all_content(property)
+        .map_err(|_| PropertyValueError::UnknownProperty)?;
+    let url_data = super::dummy_url_data();
+    let mut declarations = SourcePropertyDeclaration::default();
+    parse_one_declaration_into(
+        &mut declarations,
+        id,
+        value,
+        Origin::Author,
+        &url_data,
+        None,
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        CssRuleType::Style,
+    )
+    .map_err(|_| PropertyValueError::InvalidValue)?;
+    if declarations.declarations.is_empty() {
+        // the `all` shorthand is stored separately
+        return Ok(vec![Declaration {
+            property: property.to_ascii_lowercase(),
+            value: value.trim().to_owned(),
+            important: false,
+        }]);
+    }
+    Ok(declarations
+        .declarations
+        .iter()
+        .map(to_declaration)
+        .collect())
+}
+
+fn to_declaration(declaration: &PropertyDeclaration) -> Declaration {
+    let mut value = String::new();
+    // writing into a string can't fail
+    let _ = declaration.to_css(&mut value);
+    Declaration {
+        property: declaration.id().name().into_owned(),
+        value,
+        important: false,
+    }
+}
@@ -0,0 +1,280 @@
+use cssparser::ToCss;
+use nu_protocol::{Record, Span, Value};
+use selectors::parser::{Combinator, Component, Selector};
+use style::selector_parser::{NonTSPseudoClass, SelectorImpl, SelectorParser};
+
+use super::CssError;
+
+pub struct Compound {
+    /// the combinator between this compound and the previous one
+    pub combinator: Option<&'static str>,
+    /// `(type, css)`
+    pub components: Vec<(&'static str, String)>,
+}
+
+pub struct SelectorInfo {
+    pub css: String,
+    /// `(ids, classes/attributes/pseudo-classes, types/pseudo-elements)`
+    pub specificity: (u32, u32, u32),
+    pub compounds: Vec<Compound>,
+    pub pseudo_classes: Vec<String>,
+    pub pseudo_elements: Vec<String>,
+    /// depends on state which is not part of the document (`:hover`, `::before`, ...)
+    pub dynamic: bool,
+}
+
+impl SelectorInfo {
+    pub fn to_nu(&self, span: Span) -> Value {
+        let mut out = Record::new();
+        out.push("selector", Value::string(&self.css, span));
+        out.push(
+            "specificity",
+            Value::list(
+                vec![
+                    Value::int(self.specificity.0 as i64, span),
+                    Value::int(self.specificity.1 as i64, span),
+                    Value::int(self.specificity.2 as i64, span),
+                ],
+                span,
+            ),
+        );
+        out.push(
+            "compounds",
+            Value::list(
+                self.compounds
+                    .iter()
+                    .map(|compound| {
+                        let mut c = Record::new();
+                        c.push(
+                            "combinator",
+                            match compound.combinator {
+                                Some(v) => Value::string(v, span),
+                                None => Value::nothing(span),
+                            },
+                        );
+                        c.push(
+                            "components",
+                            Value::list(
+                                compound
+                                    .components
+                                    .iter()
+                                    .map(|(t, v)| {
+                                        let mut r = Record::new();
+                                        r.push("type", Value::string(*t, span));
+                                        r.push("value", Value::string(v, span));
+                                        Value::record(r, span)
+                                    })
+                                    .collect(),
+                                span,
+                            ),
+                        );
+                        Value::record(c, span)
+                    })
+                    .collect(),
+                span,
+            ),
+        );
+        out.push("pseudo_classes", strings2nu(&self.pseudo_classes, span));
+        out.push("pseudo_elements", strings2nu(&self.pseudo_elements, span));
+        out.push("dynamic", Value::bool(self.dynamic, span));
+        Value::record(out, span)
+    }
+}
+
+fn strings2nu(strings: &[String], span: Span) -> Value {
+    Value::list(
+        strings.iter().map(|s| Value::string(s, span)).collect(),
+        span,
+    )
+}
+
+/// Parses a selector list using the same parser as stylo (and therefore blitz).
+pub fn parse_selector_list(selector: &str) -> Result<Vec<SelectorInfo>, CssError> {
+    let url_data = super::dummy_url_data();
+    let list = SelectorParser::parse_author_origin_no_namespace(selector, &url_data)
+        .map_err(|err| CssError::from_style_error(err, selector))?;
+    Ok(list.slice().iter().map(analyze_selector).collect())
+}
+
+/// Turns a nested selector (css nesting) into a standalone one.
+pub fn resolve_nesting(selector: &str, parent_selectors: &[String]) -> String {
+    if parent_selectors.is_empty() {
+        return selector.to_owned();
+    }
+    let parent = format!(":is({})", parent_selectors.join(", "));
+    // `&` within strings (`[title="a&b"]`) and escapes (`.a\&b`) is not a nesting selector
+    let mut out = String::with_capacity(selector.len());
+    let mut nested = false;
+    let mut quote = None;
+    let mut chars = selector.chars();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', _) => {
+                out.push(c);
+                out.extend(chars.next());
+            }
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                out.push(c);
+            }
+            (c, Some(q)) if c == q => {
+                quote = None;
+                out.push(c);
+            }
+            ('&', None) => {
+                nested = true;
+                out.push_str(&parent);
+            }
+            _ => out.push(c),
+        }
+    }
+    if nested {
+        out
+    } else {
+        format!("{parent} {selector}")
+    }
+}
+
+fn analyze_selector(selector: &Selector<SelectorImpl>) -> SelectorInfo {
+    let specificity = selector.specificity();
+    let mut info = SelectorInfo {
+        css: selector.to_css_string(),
+        specificity: (
+            specificity >> 20,
+            (specificity >> 10) & 0x3FF,
+            specificity & 0x3FF,
+        ),
+        compounds: vec![Compound {
+            combinator: None,
+            components: Vec::new(),
+        }],
+        pseudo_classes: Vec::new(),
+        pseudo_elements: Vec::new(),
+        dynamic: false,
+    };
+    // the parse-order iterator yields the compounds in order, but their components reversed.
+    // pseudo-elements are separated by an internal combinator, so each sequence between
+    // combinators is reversed on its own
+    let mut sequence_start = 0;
+    for component in selector.iter_raw_parse_order_from(0) {
+        let kind: &'static str = match component {
+            Component::Combinator(combinator) => {
+                let components = &mut info
+                    .compounds
+                    .last_mut()
+                    .expect("there is always at least one compound")
+                    .components;
+                components[sequence_start..].reverse();
+                let name = match combinator {
+                    Combinator::Child => "child",
+                    Combinator::Descendant => "descendant",
+                    Combinator::NextSibling => "next-sibling",
+                    Combinator::LaterSibling => "subsequent-sibling",
+                    // internal combinators (they are not written in the source)
+                    Combinator::PseudoElement | Combinator::SlotAssignment | Combinator::Part => {
+                        sequence_start = components.len();
+                        continue;
+                    }
+                };
+                sequence_start = 0;
+                info.compounds.push(Compound {
+                    combinator: Some(name),
+                    components: Vec::new(),
+                });
+                continue;
+            }
+            Component::RelativeSelectorAnchor | Component::ImplicitScope => continue,
+            Component::LocalName(_) => "type",
+            Component::ExplicitUniversalType => "universal",
+            Component::ID(_) => "id",
+            Component::Class(_) => "class",
+            Component::AttributeInNoNamespaceExists { .. }
+            | Component::AttributeInNoNamespace { .. }
+            | Component::AttributeOther(_) => "attribute",
+            Component::ExplicitAnyNamespace
+            | Component::ExplicitNoNamespace
+            | Component::DefaultNamespace(_)
+            | Component::Namespace(..) => "namespace",
+            Component::ParentSelector => "nesting",
+            Component::PseudoElement(_) | Component::Slotted(_) | Component::Part(_) => {
+                "pseudo-element"
+            }
+            Component::Invalid(_) => "invalid",
+            _ => "pseudo-class",
+        };
+        collect_pseudos(component, &mut info);
+        info.compounds
+            .last_mut()
+            .expect("there is always at least one compound")
+            .components
+            .push((kind, component.to_css_string()));
+    }
+    info.compounds
+        .last_mut()
+        .expect("there is always at least one compound")
+        .components[sequence_start..]
+        .reverse();
+    info
+}
+
+/// Records the pseudo-classes/-elements used by a component (including within `:is()` & co).
+fn collect_pseudos(component: &Component<SelectorImpl>, info: &mut SelectorInfo) {
+    let nested: Vec<&Selector<SelectorImpl>> = match component {
+        Component::PseudoElement(_) | Component::Slotted(_) | Component::Part(_) => {
+            let css = component.to_css_string();
+            info.pseudo_elements
+                .push(css.split('(').next().unwrap_or(&css).to_owned());
+            info.dynamic = true;
+            return;
+        }
+        Component::NonTSPseudoClass(pc) => {
+            if is_dynamic_pseudo_class(pc) {
+                info.dynamic = true;
+            }
+            Vec::new()
+        }
+        Component::Negation(list) | Component::Is(list) | Component::Where(list) => {
+            list.slice().iter().collect()
+        }
+        Component::NthOf(nth) => nth.selectors().iter().collect(),
+        Component::Has(relative) => relative.iter().map(|r| &r.selector).collect(),
+        Component::Root
+        | Component::Empty
+        | Component::Scope
+        | Component::Nth(_)
+        | Component::Host(_) => Vec::new(),
+        _ => {
+            return;
+        }
+    };
+    let css = component.to_css_string();
+    let name = css.split('(').next().unwrap_or(&css);
+    if !info.pseudo_classes.iter().any(|i| i == name) {
+        info.pseudo_classes.push(name.to_owned());
+    }
+    for selector in nested {
+        for component in selector.iter_raw_match_order() {
+            collect_pseudos(component, info);
+        }
+    }
+}
+
+fn is_dynamic_pseudo_class(pc: &NonTSPseudoClass) -> bool {
+    matches!(
+        pc,
+        NonTSPseudoClass::Active
+            | NonTSPseudoClass::Autofill
+            | NonTSPseudoClass::CustomState(_)
+            | NonTSPseudoClass::Focus
+            | NonTSPseudoClass::FocusVisible
+            | NonTSPseudoClass::FocusWithin
+            | NonTSPseudoClass::Fullscreen
+            | NonTSPseudoClass::Hover
+            | NonTSPseudoClass::Modal
+            | NonTSPseudoClass::PopoverOpen
+            | NonTSPseudoClass::Target
+            | NonTSPseudoClass::UserInvalid
+            | NonTSPseudoClass::UserValid
+            | NonTSPseudoClass::Visited
+    )
+}
@@ -0,0 +1,413 @@
+use cssparser::{
+    AtRuleParser, CowRcStr, DeclarationParser, ParseError, Parser, ParserInput, ParserState,
+    QualifiedRuleParser, RuleBodyItemParser, RuleBodyParser, StyleSheetParser, Token,
+};
+use nu_protocol::{Record, Span, Value};
+
+use super::CssError;
+
+#[derive(Clone)]
+pub struct Declaration {
+    pub property: String,
+    pub value: String,
+    pub important: bool,
+}
+
+impl Declaration {
+    pub fn to_nu(&self, span: Span) -> Value {
+        let mut out = Record::new();
+        out.push("property", Value::string(&self.property, span));
+        out.push("value", Value::string(&self.value, span));
+        out.push("important", Value::bool(self.important, span));
+        Value::record(out, span)
+    }
+
+    pub fn to_css(&self) -> String {
+        if self.important {
+            format!("{}: {} !important", self.property, self.value)
+        } else {
+            format!("{}: {}", self.property, self.value)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Rule {
+    Style {
+        selectors: Vec<String>,
+        declarations: Vec<Declaration>,
+        /// nested rules (css nesting)
+        rules: Vec<Rule>,
+    },
+    At {
+        /// lowercase and without the `@`
+        name: String,
+        prelude: String,
+        /// `None` for statements such as `@import`
+        block: Option<(Vec<Declaration>, Vec<Rule>)>,
+    },
+}
+
+impl Rule {
+    pub fn to_nu(&self, span: Span) -> Value {
+        let mut out = Record::new();
+        match self {
+            Rule::Style {
+                selectors,
+                declarations,
+                rules,
+            } => {
+                out.push("type", Value::string("style", span));
+                out.push("name", Value::nothing(span));
+                out.push(
+                    "selectors",
+                    Value::list(
+                        selectors.iter().map(|s| Value::string(s, span)).collect(),
+                        span,
+                    ),
+                );
+                out.push("prelude", Value::nothing(span));
+                out.push("declarations", declarations2nu(declarations, span));
+                out.push("rules", rules2nu(rules, span));
+            }
+            Rule::At {
+                name,
+                prelude,
+                block,
+            } => {
+                out.push("type", Value::string("at-rule", span));
+                out.push("name", Value::string(name, span));
+                out.push("selectors", Value::nothing(span));
+                out.push("prelude", Value::string(prelude, span));
+                match block {
+                    Some((declarations, rules)) => {
+                        out.push("declarations", declarations2nu(declarations, span));
+                        out.push("rules", rules2nu(rules, span));
+                    }
+                    None => {
+                        out.push("declarations", Value::nothing(span));
+                        out.push("rules", Value::nothing(span));
+                    }
+                }
+            }
+        }
+        Value::record(out, span)
+    }
+}
+
+impl Rule {
+    pub fn to_css(&self) -> String {
+        match self {
+            Rule::Style {
+                selectors,
+                declarations,
+                rules,
+            } => format!(
+                "{} {{ {} }}",
+                selectors.join(", "),
+                block2css(declarations, rules)
+            ),
+            Rule::At {
+                name,
+                prelude,
+                block: None,
+            } => format!("{};", at_rule_head(name, prelude)),
+            Rule::At {
+                name,
+                prelude,
+                block: Some((declarations, rules)),
+            } => format!(
+                "{} {{ {} }}",
+                at_rule_head(name, prelude),
+                block2css(declarations, rules)
+            ),
+        }
+    }
+}
+
+fn at_rule_head(name: &str, prelude: &str) -> String {
+    if prelude.is_empty() {
+        format!("@{name}")
+    } else {
+        format!("@{name} {prelude}")
+    }
+}
+
+fn block2css(declarations: &[Declaration], rules: &[Rule]) -> String {
+    declarations
+        .iter()
+        .map(|d| format!("{};", d.to_css()))
+        .chain(rules.iter().map(|r| r.to_css()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn declarations2nu(declarations: &[Declaration], span: Span) -> Value {
+    Value::list(declarations.iter().map(|d| d.to_nu(span)).collect(), span)
+}
+
+pub fn rules2nu(rules: &[Rule], span: Span) -> Value {
+    Value::list(rules.iter().map(|r| r.to_nu(span)).collect(), span)
+}
+
+pub struct Stylesheet {
+    pub rules: Vec<Rule>,
+    pub errors: Vec<CssError>,
+}
+
+pub fn parse_stylesheet(css: &str) -> Stylesheet {
+    let mut errors: Vec<CssError> = Vec::new();
+    let mut input = ParserInput::new(css);
+    let mut input = Parser::new(&mut input);
+    let mut parser = RuleParser {
+        errors: &mut errors,
+        mode: BlockMode::TopLevel,
+    };
+    let mut rules: Vec<Rule> = Vec::new();
+    let mut found_errors: Vec<CssError> = Vec::new();
+    for item in StyleSheetParser::new(&mut input, &mut parser) {
+        match item {
+            Ok(Item::Rule(rule)) => rules.push(rule),
+            Ok(Item::Declaration(_)) => unreachable!("declarations are not parsed at top-level"),
+            Err((err, source)) => found_errors.push(CssError::new(err, source)),
+        }
+    }
+    errors.extend(found_errors);
+    errors.sort_by_key(|e| (e.line, e.column));
+    Stylesheet { rules, errors }
+}
+
+/// Parses the content of a declaration block (such as a `style=""` attribute).
+pub fn parse_declaration_list(css: &str) -> (Vec<Declaration>, Vec<CssError>) {
+    let mut errors: Vec<CssError> = Vec::new();
+    let mut input = ParserInput::new(css);
+    let mut input = Parser::new(&mut input);
+    let (declarations, _rules) =
+        parse_block_content(&mut input, &mut errors, BlockMode::Declarations);
+    errors.sort_by_key(|e| (e.line, e.column));
+    (declarations, errors)
+}
+
+enum Item {
+    Declaration(Declaration),
+    Rule(Rule),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum BlockMode {
+    /// rules only (stylesheet)
+    TopLevel,
+    /// rules only (`@media`, `@keyframes`, ...)
+    Rules,
+    /// declarations and nested rules
+    Style,
+    /// declarations only (`@font-face`, `style=""`, ...)
+    Declarations,
+}
+
+struct RuleParser<'a> {
+    errors: &'a mut Vec<CssError>,
+    mode: BlockMode,
+}
+
+fn parse_block_content(
+    input: &mut Parser<'_, '_>,
+    errors: &mut Vec<CssError>,
+    mode: BlockMode,
+) -> (Vec<Declaration>, Vec<Rule>) {
+    let mut declarations: Vec<Declaration> = Vec::new();
+    let mut rules: Vec<Rule> = Vec::new();
+    let mut found_errors: Vec<CssError> = Vec::new();
+    let mut parser = RuleParser { errors, mode };
+    for item in RuleBodyParser::new(input, &mut parser) {
+        match item {
+            Ok(Item::Declaration(declaration)) => declarations.push(declaration),
+            Ok(Item::Rule(rule)) => rules.push(rule),
+            Err((err, source)) => found_errors.push(CssError::new(err, source)),
+        }
+    }
+    errors.extend(found_errors);
+    (declarations, rules)
+}
+
+/// Consumes everything until the end of the (delimited) input and returns it as string.
+fn consume_all<'i>(input: &mut Parser<'i, '_>) -> &'i str {
+    let start = input.position();
+    while input.next().is_ok() {}
+    input.slice_from(start).trim()
+}
+
+impl<'i> DeclarationParser<'i> for RuleParser<'_> {
+    type Declaration = Item;
+    type Error = String;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+        _declaration_start: &ParserState,
+    ) -> Result<Self::Declaration, ParseError<'i, Self::Error>> {
+        let is_custom_property = name.starts_with("--");
+        let start = input.position();
+        let mut important = false;
+        loop {
+            if input
+                .try_parse(|i| -> Result<(), ParseError<'i, Self::Error>> {
+                    cssparser::parse_important(i)?;
+                    i.expect_exhausted()?;
+                    Ok(())
+                })
+                .is_ok()
+            {
+                important = true;
+                break;
+            }
+            match input.next() {
+                Ok(Token::CurlyBracketBlock) if !is_custom_property => {
+                    // most likely a nested rule
+                    return Err(
+                        input.new_custom_error(String::from("unexpected '{' in declaration value"))
+                    );
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        let value = input.slice_from(start);
+        let value = match value.rfind('!') {
+            Some(i) if important => &value[..i],
+            _ => value,
+        }
+        .trim();
+        if value.is_empty() && !is_custom_property {
+            return Err(input.new_custom_error(format!("missing value for '{name}'")));
+        }
+        Ok(Item::Declaration(Declaration {
+            property: if is_custom_property {
+                name.to_string()
+            } else {
+                name.to_ascii_lowercase()
+            },
+            value: value.to_owned(),
+            important,
+        }))
+    }
+}
+
+impl<'i> QualifiedRuleParser<'i> for RuleParser<'_> {
+    type Prelude = Vec<String>;
+    type QualifiedRule = Item;
+    type Error = String;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+        input.parse_comma_separated(|i| -> Result<String, ParseError<'i, Self::Error>> {
+            let selector = consume_all(i);
+            if selector.is_empty() {
+                return Err(i.new_custom_error(String::from("empty selector")));
+            }
+            Ok(selector.to_owned())
+        })
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        prelude: Self::Prelude,
+        _start: &ParserState,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::QualifiedRule, ParseError<'i, Self::Error>> {
+        let (declarations, rules) = parse_block_content(input, self.errors, BlockMode::Style);
+        Ok(Item::Rule(Rule::Style {
+            selectors: prelude,
+            declarations,
+            rules,
+        }))
+    }
+}
+
+impl<'i> AtRuleParser<'i> for RuleParser<'_> {
+    type Prelude = (String, String);
+    type AtRule = Item;
+    type Error = String;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+        Ok((name.to_ascii_lowercase(), consume_all(input).to_owned()))
+    }
+
+    fn rule_without_block(
+        &mut self,
+        prelude: Self::Prelude,
+        _start: &ParserState,
+    ) -> Result<Self::AtRule, ()> {
+        Ok(Item::Rule(Rule::At {
+            name: prelude.0,
+            prelude: prelude.1,
+            block: None,
+        }))
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        prelude: Self::Prelude,
+        _start: &ParserState,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::AtRule, ParseError<'i, Self::Error>> {
+        let unprefixed: &str = if prelude.0.starts_with('-') {
+            prelude.0.splitn(3, '-').nth(2).unwrap_or(&prelude.0)
+        } else {
+            &prelude.0
+        };
+        let mode = match unprefixed {
+            "font-face"
+            | "page"
+            | "counter-style"
+            | "property"
+            | "font-palette-values"
+            | "font-feature-values"
+            | "viewport"
+            | "position-try"
+            | "view-transition"
+            | "top-left-corner"
+            | "top-left"
+            | "top-center"
+            | "top-right"
+            | "top-right-corner"
+            | "bottom-left-corner"
+            | "bottom-left"
+            | "bottom-center"
+            | "bottom-right"
+            | "bottom-right-corner"
+            | "left-top"
+            | "left-middle"
+            | "left-bottom"
+            | "right-top"
+            | "right-middle"
+            | "right-bottom" => BlockMode::Declarations,
+            "keyframes" => BlockMode::Rules,
+            _ if self.mode == BlockMode::Style => BlockMode::Style,
+            _ => BlockMode::Rules,
+        };
+        let block = parse_block_content(input, self.errors, mode);
+        Ok(Item::Rule(Rule::At {
+            name: prelude.0,
+            prelude: prelude.1,
+            block: Some(block),
+        }))
+    }
+}
+
+impl<'i> RuleBodyItemParser<'i, Item, String> for RuleParser<'_> {
+    fn parse_declarations(&self) -> bool {
+        matches!(self.mode, BlockMode::Style | BlockMode::Declarations)
+    }
+
+    fn parse_qualified(&self) -> bool {
+        self.mode != BlockMode::Declarations
+    }
+}
@@ -0,0 +1,129 @@
+use cssparser::{ParseError, Parser, ParserInput, Token, serialize_string};
+
+/// Calls `rewrite` with every url of a stylesheet (or declaration list): `url()`s and `@import "..."`.
+/// Urls for which it returns `Some` are replaced, everything else is kept as it is.
+pub fn rewrite_urls(css: &str, rewrite: &mut impl FnMut(&str) -> Option<String>) -> String {
+    rewrite_urls_with_property(css, &mut |url, _| rewrite(url))
+}
+
+/// Calls `found` with every url of a stylesheet (or declaration list) and the property
+/// it is used in (`None` for `@import`).
+pub fn find_urls(css: &str, found: &mut impl FnMut(&str, Option<&str>)) {
+    rewrite_urls_with_property(css, &mut |url, property| {
+        found(url, property);
+        None
+    });
+}
+
+fn rewrite_urls_with_property(
+    css: &str,
+    rewrite: &mut impl FnMut(&str, Option<&str>) -> Option<String>,
+) -> String {
+    let mut input = ParserInput::new(css);
+    let mut input = Parser::new(&mut input);
+    let mut rewriter = Rewriter {
+        css,
+        out: String::with_capacity(css.len()),
+        copied: 0,
+        property: None,
+        rewrite,
+    };
+    rewriter.tokens(&mut input);
+    rewriter.out.push_str(&css[rewriter.copied..]);
+    rewriter.out
+}
+
+struct Rewriter<'c, 'f, F> {
+    css: &'c str,
+    out: String,
+    /// the input up to here is in `out`
+    copied: usize,
+    /// the property of the current declaration
+    property: Option<String>,
+    rewrite: &'f mut F,
+}
+
+impl<F: FnMut(&str, Option<&str>) -> Option<String>> Rewriter<'_, '_, F> {
+    fn tokens(&mut self, input: &mut Parser<'_, '_>) {
+        let mut after_import = false;
+        // an identifier at the start of a statement (which could be a property)
+        let mut statement_start = true;
+        let mut ident = None;
+        loop {
+            let start = input.position().byte_index();
+            let Ok(token) = input.next_including_whitespace_and_comments() else {
+                return;
+            };
+            let token = token.clone();
+            match &token {
+                Token::UnquotedUrl(url) => self.replace(start, input.position().byte_index(), url),
+                Token::QuotedString(url) if after_import => {
+                    self.replace(start, input.position().byte_index(), url)
+                }
+                Token::Function(name) if name.eq_ignore_ascii_case("url") => {
+                    let mut url = None;
+                    let _ = input.parse_nested_block(|i| -> Result<(), ParseError<'_, ()>> {
+                        if let Ok(s) = i.expect_string() {
+                            url = Some(s.to_string());
+                        }
+                        Ok(())
+                    });
+                    if let Some(url) = url {
+                        self.replace(start, input.position().byte_index(), &url);
+                    }
+                }
+                Token::Function(_)
+                | Token::ParenthesisBlock
+                | Token::SquareBracketBlock
+                | Token::CurlyBracketBlock => {
+                    let property = self.property.clone();
+                    if matches!(token, Token::CurlyBracketBlock) {
+                        self.property = None;
+                    }
+                    let _ = input.parse_nested_block(|i| -> Result<(), ParseError<'_, ()>> {
+                        self.tokens(i);
+                        Ok(())
+                    });
+                    self.property = property;
+                }
+                _ => {}
+            }
+            match token {
+                Token::WhiteSpace(_) | Token::Comment(_) => {}
+                Token::AtKeyword(name) => {
+                    after_import = name.eq_ignore_ascii_case("import");
+                    statement_start = false;
+                }
+                Token::Ident(name) if statement_start => {
+                    ident = Some(name.to_string());
+                    statement_start = false;
+                }
+                Token::Colon if ident.is_some() => self.property = ident.take(),
+                Token::Semicolon | Token::CurlyBracketBlock => {
+                    statement_start = true;
+                    ident = None;
+                    self.property = None;
+                    after_import = false;
+                }
+                _ => {
+                    after_import = false;
+                    statement_start = false;
+                    ident = None;
+                }
+            }
+        }
+    }
+
+    /// Replaces `css[start..end]` if `rewrite` returns a new url.
+    fn replace(&mut self, start: usize, end: usize, url: &str) {
+        let Some(new) = (self.rewrite)(url, self.property.as_deref()) else {
+            return;
+        };
+        self.out.push_str(&self.css[self.copied..start]);
+        self.out.push_str("url(");
+        // writing into a string can't fail
+        let _ = serialize_string(&new, &mut self.out);
+        self.out.push(')');
+        self.copied = end;
+    }
+}
@@ -0,0 +1,209 @@
+use std::borrow::Cow;
+
+use cssparser::{ParseError, Parser, ParserInput, Token};
+use nu_protocol::{Record, Span, Value};
+use style::context::QuirksMode;
+use style::parser::{Parse, ParserContext};
+use style::stylesheets::{CssRuleType, Namespaces, Origin, UrlExtraData};
+use style::values::specified::Color;
+use style_traits::{ParsingMode, ToCss};
+
+/// Turns a (serialized) value of a longhand property into typed nu values:
+/// numbers, `{value, unit}` for dimensions and percentages, `{r, g, b, alpha}` for colors
+/// (only for properties which take colors), `{url}`, `{function, arguments}` and strings for everything else.
+/// Comma- and space-separated values become lists.
+pub fn value2nu(property: &str, css: &str, span: Span) -> Value {
+    let url_data = super::dummy_url_data();
+    let context = parser_context(&url_data);
+    let grammar = Grammar::of(property);
+    let mut input = ParserInput::new(css);
+    let mut input = Parser::new(&mut input);
+    parse_comma_separated(&context, grammar, &mut input, span)
+}
+
+/// What the components of a property value are, as far as it matters for typing them.
+#[derive(Clone, Copy, PartialEq)]
+enum Grammar {
+    /// identifiers can be named colors (`red`)
+    Colors,
+    /// `font-family`: identifiers separated by spaces are one family name
+    FamilyNames,
+    Other,
+}
+
+impl Grammar {
+    fn of(property: &str) -> Self {
+        let property = property.to_ascii_lowercase();
+        match property.as_str() {
+            "font-family" => Grammar::FamilyNames,
+            // <color> itself and values containing colors (shadows, gradients, drop-shadow())
+            "fill"
+            | "stroke"
+            | "box-shadow"
+            | "text-shadow"
+            | "background-image"
+            | "mask-image"
+            | "border-image-source"
+            | "list-style-image"
+            | "filter"
+            | "backdrop-filter" => Grammar::Colors,
+            _ if property.ends_with("color") => Grammar::Colors,
+            _ => Grammar::Other,
+        }
+    }
+}
+
+fn parser_context(url_data: &UrlExtraData) -> ParserContext<'_> {
+    ParserContext::new(
+        Origin::Author,
+        url_data,
+        Some(CssRuleType::Style),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        Cow::Owned(Namespaces::default()),
+        None,
+        None,
+    )
+}
+
+fn parse_comma_separated(
+    context: &ParserContext,
+    grammar: Grammar,
+    input: &mut Parser<'_, '_>,
+    span: Span,
+) -> Value {
+    let mut groups = parse_arguments(context, grammar, input, span);
+    if groups.len() == 1 {
+        groups.remove(0)
+    } else {
+        Value::list(groups, span)
+    }
+}
+
+/// Parses comma-separated groups of space-separated components.
+fn parse_arguments(
+    context: &ParserContext,
+    grammar: Grammar,
+    input: &mut Parser<'_, '_>,
+    span: Span,
+) -> Vec<Value> {
+    let mut groups: Vec<Value> = Vec::new();
+    loop {
+        let group = input.parse_until_before(cssparser::Delimiter::Comma, |i| {
+            Ok::<_, ParseError<'_, ()>>(parse_components(context, grammar, i, span))
+        });
+        if let Ok(group) = group {
+            groups.push(group);
+        }
+        if input.next().is_err() {
+            break;
+        }
+    }
+    groups
+}
+
+fn parse_components(
+    context: &ParserContext,
+    grammar: Grammar,
+    input: &mut Parser<'_, '_>,
+    span: Span,
+) -> Value {
+    let mut components: Vec<Value> = Vec::new();
+    while !input.is_exhausted() {
+        if grammar == Grammar::Colors
+            && let Ok(color) = input.try_parse(|i| Color::parse(context, i))
+        {
+            match color.resolve_to_absolute() {
+                Some(color) => components.push(color2nu(color, span)),
+                // `currentcolor`, system colors, ...
+                None => components.push(Value::string(color.to_css_string(), span)),
+            }
+            continue;
+        }
+        let start = input.position();
+        let token = match input.next() {
+            Ok(token) => token.clone(),
+            Err(_) => break,
+        };
+        components.push(match token {
+            Token::Number {
+                int_value: Some(v), ..
+            } => Value::int(v as i64, span),
+            Token::Number { value, .. } => Value::float(to_f64(value), span),
+            Token::Percentage { unit_value, .. } => dimension2nu(unit_value * 100.0, "%", span),
+            Token::Dimension { value, unit, .. } => {
+                dimension2nu(value, &unit.to_ascii_lowercase(), span)
+            }
+            Token::QuotedString(s) | Token::Ident(s) => Value::string(s.as_ref(), span),
+            Token::UnquotedUrl(url) => url2nu(&url, span),
+            Token::Function(name) if name.eq_ignore_ascii_case("url") => {
+                let url = input
+                    .parse_nested_block(|i| {
+                        i.expect_string()
+                            .map(|s| s.to_string())
+                            .map_err(ParseError::<'_, ()>::from)
+                    })
+                    .unwrap_or_default();
+                url2nu(&url, span)
+            }
+            Token::Function(name) => {
+                let arguments = input
+                    .parse_nested_block(|i| {
+                        Ok::<_, ParseError<'_, ()>>(parse_arguments(context, grammar, i, span))
+                    })
+                    .unwrap_or_default();
+                let mut r = Record::new();
+                r.push("function", Value::string(name.to_ascii_lowercase(), span));
+                r.push("arguments", Value::list(arguments, span));
+                Value::record(r, span)
+            }
+            Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+                // skip the content of the block to include it in the slice
+                let _ = input.parse_nested_block(|i| {
+                    while i.next().is_ok() {}
+                    Ok::<_, ParseError<'_, ()>>(())
+                });
+                Value::string(input.slice_from(start).trim(), span)
+            }
+            _ => Value::string(input.slice_from(start).trim(), span),
+        });
+    }
+    if grammar == Grammar::FamilyNames && components.iter().all(|c| c.as_str().is_ok()) {
+        // `Red Hat Display`
+        let names: Vec<&str> = components.iter().filter_map(|c| c.as_str().ok()).collect();
+        Value::string(names.join(" "), span)
+    } else if components.len() == 1 {
+        components.remove(0)
+    } else {
+        Value::list(components, span)
+    }
+}
+
+fn dimension2nu(value: f32, unit: &str, span: Span) -> Value {
+    let mut r = Record::new();
+    r.push("value", Value::float(to_f64(value), span));
+    r.push("unit", Value::string(unit, span));
+    Value::record(r, span)
+}
+
+fn url2nu(url: &str, span: Span) -> Value {
+    let mut r = Record::new();
+    r.push("url", Value::string(url, span));
+    Value::record(r, span)
+}
+
+fn color2nu(color: style::color::AbsoluteColor, span: Span) -> Value {
+    let color = color.into_srgb_legacy();
+    let channel = |v: f32| Value::int((v.clamp(0.0, 1.0) * 255.0).round() as i64, span);
+    let mut r = Record::new();
+    r.push("r", channel(color.components.0));
+    r.push("g", channel(color.components.1));
+    r.push("b", channel(color.components.2));
+    r.push("alpha", Value::float(to_f64(color.alpha), span));
+    Value::record(r, span)
+}
+
+/// `0.1f32 as f64` is `0.10000000149011612`, the shortest representation of the f32 is nicer.
+fn to_f64(v: f32) -> f64 {
+    v.to_string().parse().unwrap_or(v as f64)
+}
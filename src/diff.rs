@@ -0,0 +1,483 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use ego_tree::NodeRef;
+use scraper::{ElementRef, Node};
+
+use crate::format_qual_name;
+use crate::serialize::{Syntax, serialize_node};
+
+pub struct DiffOptions {
+    /// how removed and inserted nodes are serialized
+    pub syntax: Syntax,
+    /// skip text nodes which only contain whitespace
+    pub ignore_whitespace: bool,
+    pub ignore_comments: bool,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ChangeKind {
+    Inserted,
+    Removed,
+    Moved,
+    AttributeAdded,
+    AttributeChanged,
+    AttributeRemoved,
+    TextChanged,
+    CommentChanged,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Inserted => "inserted",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Moved => "moved",
+            ChangeKind::AttributeAdded => "attribute-added",
+            ChangeKind::AttributeChanged => "attribute-changed",
+            ChangeKind::AttributeRemoved => "attribute-removed",
+            ChangeKind::TextChanged => "text-changed",
+            ChangeKind::CommentChanged => "comment-changed",
+        }
+    }
+}
+
+pub struct Change {
+    pub kind: ChangeKind,
+    /// css path of the changed element (of the parent element for text and comments),
+    /// in the new document except for removed nodes
+    pub path: String,
+    pub attribute: Option<String>,
+    /// the old node/attribute value/text (the old path for moved nodes)
+    pub old: Option<String>,
+    /// the new node/attribute value/text (the new path for moved nodes)
+    pub new: Option<String>,
+}
+
+/// Compares the children of `old` and `new` (usually the document nodes).
+/// Paths are relative to these nodes.
+pub fn diff(old: NodeRef<'_, Node>, new: NodeRef<'_, Node>, options: &DiffOptions) -> Vec<Change> {
+    let mut differ = Differ {
+        options,
+        old_root: old,
+        new_root: new,
+        changes: Vec::new(),
+        removed: Vec::new(),
+        inserted: Vec::new(),
+    };
+    differ.diff_children(old, new);
+    differ.find_moves();
+    differ.changes.into_iter().flatten().collect()
+}
+
+struct Differ<'a, 'o> {
+    options: &'o DiffOptions,
+    old_root: NodeRef<'a, Node>,
+    new_root: NodeRef<'a, Node>,
+    /// `None` for inserted nodes which turned out to be moved
+    changes: Vec<Option<Change>>,
+    /// (index in changes, node, fingerprint)
+    removed: Vec<(usize, NodeRef<'a, Node>, u64)>,
+    inserted: Vec<(usize, NodeRef<'a, Node>, u64)>,
+}
+
+#[derive(Copy, Clone)]
+struct Child<'a> {
+    node: NodeRef<'a, Node>,
+    /// preceding whitespace-only text
+    whitespace_before: Option<NodeRef<'a, Node>>,
+}
+
+/// One step of an alignment of two sequences.
+enum Step {
+    Old(usize),
+    New(usize),
+    Both(usize, usize),
+}
+
+impl<'a> Differ<'a, '_> {
+    /// The children which are compared (`children()` without ignored nodes).
+    fn relevant_children(&self, node: NodeRef<'a, Node>) -> Vec<NodeRef<'a, Node>> {
+        node.children()
+            .filter(|child| match child.value() {
+                Node::Text(text) => !(self.options.ignore_whitespace && text.trim().is_empty()),
+                Node::Comment(_) => !self.options.ignore_comments,
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// The children without whitespace-only text, which is attached to the following child
+    /// (so that indentation doesn't influence how the children are matched), and the trailing whitespace.
+    fn children(&self, node: NodeRef<'a, Node>) -> (Vec<Child<'a>>, Option<NodeRef<'a, Node>>) {
+        let mut children = Vec::new();
+        let mut whitespace = None;
+        for child in self.relevant_children(node) {
+            match child.value() {
+                Node::Text(text) if text.trim().is_empty() => whitespace = Some(child),
+                _ => children.push(Child {
+                    node: child,
+                    whitespace_before: whitespace.take(),
+                }),
+            }
+        }
+        (children, whitespace)
+    }
+
+    /// Hash of the node and its (relevant) descendants, equal for equal subtrees.
+    fn fingerprint(&self, node: NodeRef<'a, Node>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        match node.value() {
+            Node::Element(element) => {
+                element.name.hash(&mut hasher);
+                let mut attrs: Vec<_> = element.attrs.iter().collect();
+                attrs.sort();
+                attrs.hash(&mut hasher);
+                for child in self.relevant_children(node) {
+                    self.fingerprint(child).hash(&mut hasher);
+                }
+            }
+            Node::Text(text) => ("#text", &**text).hash(&mut hasher),
+            Node::Comment(comment) => ("#comment", &**comment).hash(&mut hasher),
+            Node::Doctype(doctype) => (
+                "#doctype",
+                doctype.name(),
+                doctype.public_id(),
+                doctype.system_id(),
+            )
+                .hash(&mut hasher),
+            Node::ProcessingInstruction(pi) => ("#pi", &*pi.target, &*pi.data).hash(&mut hasher),
+            Node::Document | Node::Fragment => {}
+        }
+        hasher.finish()
+    }
+
+    /// Nodes with the same key are compared with each other (instead of being removed and inserted).
+    fn key(node: NodeRef<'a, Node>) -> String {
+        match node.value() {
+            Node::Element(element) => format!(
+                "<{}:{}#{}",
+                element.name.ns,
+                element.name.local,
+                element.id().unwrap_or_default()
+            ),
+            Node::Text(_) => String::from("#text"),
+            Node::Comment(_) => String::from("#comment"),
+            Node::Doctype(_) => String::from("#doctype"),
+            Node::ProcessingInstruction(pi) => format!("?{}", &*pi.target),
+            Node::Document | Node::Fragment => String::new(),
+        }
+    }
+
+    fn diff_children(&mut self, old_parent: NodeRef<'a, Node>, new_parent: NodeRef<'a, Node>) {
+        let (old, old_trailing) = self.children(old_parent);
+        let (new, new_trailing) = self.children(new_parent);
+        let old_fingerprints: Vec<u64> = old.iter().map(|c| self.fingerprint(c.node)).collect();
+        let new_fingerprints: Vec<u64> = new.iter().map(|c| self.fingerprint(c.node)).collect();
+
+        // unchanged nodes are the anchors, the nodes between them are compared by key
+        let mut old_gap = Vec::new();
+        let mut new_gap = Vec::new();
+        for step in align(&old_fingerprints, &new_fingerprints) {
+            match step {
+                Step::Old(i) => old_gap.push(old[i]),
+                Step::New(j) => new_gap.push(new[j]),
+                Step::Both(i, j) => {
+                    self.diff_gap(&old_gap, &new_gap);
+                    old_gap.clear();
+                    new_gap.clear();
+                    self.diff_whitespace(old[i].whitespace_before, new[j].whitespace_before);
+                }
+            }
+        }
+        self.diff_gap(&old_gap, &new_gap);
+        self.diff_whitespace(old_trailing, new_trailing);
+    }
+
+    fn diff_gap(&mut self, old: &[Child<'a>], new: &[Child<'a>]) {
+        let old_keys: Vec<String> = old.iter().map(|c| Self::key(c.node)).collect();
+        let new_keys: Vec<String> = new.iter().map(|c| Self::key(c.node)).collect();
+        for step in align(&old_keys, &new_keys) {
+            match step {
+                // whitespace goes together with the node
+                Step::Old(i) => self.push_removed(old[i].node),
+                Step::New(j) => self.push_inserted(new[j].node),
+                Step::Both(i, j) => {
+                    self.diff_whitespace(old[i].whitespace_before, new[j].whitespace_before);
+                    self.diff_node(old[i].node, new[j].node);
+                }
+            }
+        }
+    }
+
+    fn diff_whitespace(&mut self, old: Option<NodeRef<'a, Node>>, new: Option<NodeRef<'a, Node>>) {
+        match (old, new) {
+            (Some(old), Some(new)) => self.diff_node(old, new),
+            (Some(old), None) => self.push_removed(old),
+            (None, Some(new)) => self.push_inserted(new),
+            (None, None) => {}
+        }
+    }
+
+    fn diff_node(&mut self, old: NodeRef<'a, Node>, new: NodeRef<'a, Node>) {
+        match (old.value(), new.value()) {
+            (Node::Element(old_element), Node::Element(new_element)) => {
+                let path = path(new, self.new_root);
+                for (name, old_value) in old_element.attrs.iter() {
+                    let new_value = new_element
+                        .attrs
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(_, v)| v);
+                    let kind = match new_value {
+                        Some(new_value) if new_value == old_value => continue,
+                        Some(_) => ChangeKind::AttributeChanged,
+                        None => ChangeKind::AttributeRemoved,
+                    };
+                    self.push(Change {
+                        kind,
+                        path: path.clone(),
+                        attribute: Some(format_qual_name(name)),
+                        old: Some(old_value.to_string()),
+                        new: new_value.map(|v| v.to_string()),
+                    });
+                }
+                for (name, new_value) in new_element.attrs.iter() {
+                    if !old_element.attrs.iter().any(|(n, _)| n == name) {
+                        self.push(Change {
+                            kind: ChangeKind::AttributeAdded,
+                            path: path.clone(),
+                            attribute: Some(format_qual_name(name)),
+                            old: None,
+                            new: Some(new_value.to_string()),
+                        });
+                    }
+                }
+                self.diff_children(old, new);
+            }
+            (Node::Text(old_text), Node::Text(new_text)) => {
+                if old_text != new_text {
+                    self.push(Change {
+                        kind: ChangeKind::TextChanged,
+                        path: path(new, self.new_root),
+                        attribute: None,
+                        old: Some(old_text.to_string()),
+                        new: Some(new_text.to_string()),
+                    });
+                }
+            }
+            (Node::Comment(old_comment), Node::Comment(new_comment)) => {
+                if old_comment != new_comment {
+                    self.push(Change {
+                        kind: ChangeKind::CommentChanged,
+                        path: path(new, self.new_root),
+                        attribute: None,
+                        old: Some(old_comment.to_string()),
+                        new: Some(new_comment.to_string()),
+                    });
+                }
+            }
+            _ => {
+                if self.fingerprint(old) != self.fingerprint(new) {
+                    self.push_removed(old);
+                    self.push_inserted(new);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, change: Change) {
+        self.changes.push(Some(change));
+    }
+
+    fn push_removed(&mut self, node: NodeRef<'a, Node>) {
+        self.removed
+            .push((self.changes.len(), node, self.fingerprint(node)));
+        self.push(Change {
+            kind: ChangeKind::Removed,
+            path: path(node, self.old_root),
+            attribute: None,
+            old: Some(node_html(node, self.options.syntax)),
+            new: None,
+        });
+    }
+
+    fn push_inserted(&mut self, node: NodeRef<'a, Node>) {
+        self.inserted
+            .push((self.changes.len(), node, self.fingerprint(node)));
+        self.push(Change {
+            kind: ChangeKind::Inserted,
+            path: path(node, self.new_root),
+            attribute: None,
+            old: None,
+            new: Some(node_html(node, self.options.syntax)),
+        });
+    }
+
+    /// Replaces pairs of removed and inserted (equal) nodes with a move.
+    fn find_moves(&mut self) {
+        let mut inserted = std::mem::take(&mut self.inserted);
+        for (removed_index, removed, fingerprint) in std::mem::take(&mut self.removed) {
+            let movable = match removed.value() {
+                Node::Element(_) => true,
+                Node::Text(text) => !text.trim().is_empty(),
+                _ => false,
+            };
+            if !movable {
+                continue;
+            }
+            let Some(position) = inserted.iter().position(|(_, _, f)| *f == fingerprint) else {
+                continue;
+            };
+            let (inserted_index, _, _) = inserted.remove(position);
+            let Some(inserted_change) = self.changes[inserted_index].take() else {
+                continue;
+            };
+            let old_path = self.changes[removed_index].as_ref().map(|c| c.path.clone());
+            self.changes[removed_index] = Some(Change {
+                kind: ChangeKind::Moved,
+                path: inserted_change.path.clone(),
+                attribute: None,
+                old: old_path,
+                new: Some(inserted_change.path),
+            });
+        }
+    }
+}
+
+/// Aligns two sequences along their longest common subsequence.
+fn align<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Step> {
+    // the common prefix and suffix are aligned without computing lcs lengths
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut steps: Vec<Step> = (0..prefix).map(|i| Step::Both(i, i)).collect();
+    align_middle(old_middle, new_middle, prefix, prefix, &mut steps);
+    steps.extend((0..suffix).map(|k| Step::Both(old.len() - suffix + k, new.len() - suffix + k)));
+    steps
+}
+
+/// Hirschberg's algorithm: the lcs is split where the lcs lengths of the halves of `old`
+/// add up to the maximum, which needs linear memory (instead of a table of all lengths).
+/// The offsets are the positions of the slices in the whole sequences.
+fn align_middle<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    old_offset: usize,
+    new_offset: usize,
+    steps: &mut Vec<Step>,
+) {
+    if old.is_empty() {
+        steps.extend((0..new.len()).map(|j| Step::New(new_offset + j)));
+        return;
+    }
+    if new.is_empty() {
+        steps.extend((0..old.len()).map(|i| Step::Old(old_offset + i)));
+        return;
+    }
+    if old.len() == 1 {
+        match new.iter().position(|n| *n == old[0]) {
+            Some(k) => {
+                steps.extend((0..k).map(|j| Step::New(new_offset + j)));
+                steps.push(Step::Both(old_offset, new_offset + k));
+                steps.extend((k + 1..new.len()).map(|j| Step::New(new_offset + j)));
+            }
+            None => {
+                steps.push(Step::Old(old_offset));
+                steps.extend((0..new.len()).map(|j| Step::New(new_offset + j)));
+            }
+        }
+        return;
+    }
+
+    let middle = old.len() / 2;
+    let forward = lcs_lengths(old[..middle].iter(), new.iter());
+    let backward = lcs_lengths(old[middle..].iter().rev(), new.iter().rev());
+    let split = (0..=new.len())
+        .max_by_key(|&k| (forward[k] + backward[new.len() - k], std::cmp::Reverse(k)))
+        .unwrap_or(0);
+    align_middle(&old[..middle], &new[..split], old_offset, new_offset, steps);
+    align_middle(
+        &old[middle..],
+        &new[split..],
+        old_offset + middle,
+        new_offset + split,
+        steps,
+    );
+}
+
+/// `lengths[j]`: the length of the lcs of `old` and the first `j` items of `new`.
+fn lcs_lengths<'t, T: PartialEq + 't>(
+    old: impl Iterator<Item = &'t T>,
+    new: impl Iterator<Item = &'t T>,
+) -> Vec<usize> {
+    let new: Vec<&T> = new.collect();
+    let mut lengths = vec![0; new.len() + 1];
+    for o in old {
+        // lengths[j] of the previous row
+        let mut diagonal = 0;
+        for (j, n) in new.iter().enumerate() {
+            let above = lengths[j + 1];
+            lengths[j + 1] = if o == *n {
+                diagonal + 1
+            } else {
+                above.max(lengths[j])
+            };
+            diagonal = above;
+        }
+    }
+    lengths
+}
+
+/// A css path such as `html > body > ul > li:nth-of-type(2)`, up to (excluding) `root`.
+/// Text and comments get the path of their parent element.
+fn path(node: NodeRef<'_, Node>, root: NodeRef<'_, Node>) -> String {
+    let mut segments = Vec::new();
+    let mut current = Some(node);
+    while let Some(node) = current
+        && node.id() != root.id()
+    {
+        if let Node::Element(element) = node.value() {
+            let mut segment = element.name.local.to_string();
+            let same_type: Vec<_> = node
+                .parent()
+                .into_iter()
+                .flat_map(|p| p.children())
+                .filter(|s| {
+                    s.value()
+                        .as_element()
+                        .is_some_and(|e| e.name == element.name)
+                })
+                .collect();
+            if same_type.len() > 1 {
+                let position = same_type
+                    .iter()
+                    .position(|s| s.id() == node.id())
+                    .unwrap_or(0);
+                segment.push_str(&format!(":nth-of-type({})", position + 1));
+            }
+            segments.push(segment);
+        }
+        current = node.parent();
+    }
+    segments.reverse();
+    segments.join(" > ")
+}
+
+fn node_html(node: NodeRef<'_, Node>, syntax: Syntax) -> String {
+    match node.value() {
+        Node::Text(text) => text.to_string(),
+        _ if syntax == Syntax::Xml => serialize_node(node, syntax),
+        Node::Comment(comment) => format!("<!--{}-->", &**comment),
+        Node::Doctype(doctype) => format!("<!DOCTYPE {}>", doctype.name()),
+        Node::ProcessingInstruction(pi) => format!("<?{} {}?>", &*pi.target, &*pi.data),
+        Node::Element(_) => ElementRef::wrap(node).map(|e| e.html()).unwrap_or_default(),
+        Node::Document | Node::Fragment => String::new(),
+    }
+}
@@ -1,5 +1,10 @@
 pub mod commands;
 
+#[cfg(any(feature = "data_url", feature = "mime"))]
+pub mod codec;
+#[cfg(feature = "css")]
+pub mod css;
+
 #[cfg(feature = "blitz_backend")]
 mod blitz_backend;
 #[cfg(feature = "blitz_backend")]
@@ -8,6 +13,19 @@ pub use blitz_backend::BlitzBackend;
 mod scraper_backend;
 #[cfg(feature = "scraper_backend")]
 pub use scraper_backend::ScraperBackend;
+#[cfg(feature = "scraper_backend")]
+pub mod diff;
+#[cfg(feature = "mime")]
+pub mod mime_types;
+#[cfg(feature = "scraper_backend")]
+pub mod minify;
+#[cfg(feature = "mime")]
+pub mod multipart;
+#[cfg(feature = "scraper_backend")]
+pub mod serialize;
+#[cfg(feature = "mime")]
+pub mod sniff;
+pub mod srcset;
 
 pub mod plugin_interface;
 
@@ -87,3 +105,84 @@ pub trait HtmlBackend {
         span: Span,
     ) -> Result<Value, LabeledError>;
 }
+
+/// Where `HtmlBackendMut::insert_html` puts the new nodes (relative to the given node).
+#[derive(Copy, Clone, Debug)]
+pub enum InsertPosition {
+    Before,
+    After,
+}
+
+/// Modifies a parsed document in-place.
+/// Nodes are addressed by id since `HtmlBackend::Node`s borrow the document.
+pub trait HtmlBackendMut: HtmlBackend {
+    type NodeId: Copy;
+
+    fn node_id(&self, node: Self::Node<'_>) -> Self::NodeId;
+
+    /// Whether the node is still part of the document (it wasn't removed or replaced).
+    fn is_attached(&self, html: &Self::Document, node: Self::NodeId) -> bool;
+
+    fn get_attribute(
+        &self,
+        html: &Self::Document,
+        node: Self::NodeId,
+        name: &str,
+    ) -> Option<String>;
+
+    fn set_attribute(
+        &self,
+        html: &mut Self::Document,
+        node: Self::NodeId,
+        name: &str,
+        value: &str,
+    ) -> Result<(), LabeledError>;
+
+    fn remove_attribute(
+        &self,
+        html: &mut Self::Document,
+        node: Self::NodeId,
+        name: &str,
+    ) -> Result<(), LabeledError>;
+
+    /// Replaces the children of the node with the parsed `fragment`.
+    fn set_inner_html(
+        &self,
+        html: &mut Self::Document,
+        node: Self::NodeId,
+        fragment: &str,
+    ) -> Result<(), LabeledError>;
+
+    /// Replaces the children of the node with a single text node.
+    fn set_text(
+        &self,
+        html: &mut Self::Document,
+        node: Self::NodeId,
+        text: &str,
+    ) -> Result<(), LabeledError>;
+
+    fn insert_html(
+        &self,
+        html: &mut Self::Document,
+        node: Self::NodeId,
+        position: InsertPosition,
+        fragment: &str,
+    ) -> Result<(), LabeledError>;
+
+    /// Moves the node into the innermost first element of `wrapper` (`<div><p></p></div>`),
+    /// which takes its place.
+    fn wrap(
+        &self,
+        html: &mut Self::Document,
+        node: Self::NodeId,
+        wrapper: &str,
+    ) -> Result<(), LabeledError>;
+
+    /// Replaces the node with its children.
+    fn unwrap(&self, html: &mut Self::Document, node: Self::NodeId) -> Result<(), LabeledError>;
+
+    fn remove(&self, html: &mut Self::Document, node: Self::NodeId) -> Result<(), LabeledError>;
+
+    /// Serializes the whole document (including the doctype, if it was kept).
+    fn document_html(&self, html: &Self::Document) -> Result<String, LabeledError>;
+}
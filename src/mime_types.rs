@@ -0,0 +1,155 @@
+//! A compiled-in table of common mime types and their file extensions.
+
+use std::path::Path;
+
+/// Mime types with their extensions (the usual one first). When several types use
+/// the same extension, the first one is the most likely.
+const TABLE: &[(&str, &[&str])] = &[
+    // text
+    ("text/html", &["html", "htm", "shtml"]),
+    ("text/css", &["css"]),
+    ("text/javascript", &["js", "mjs", "cjs"]),
+    (
+        "text/plain",
+        &["txt", "text", "log", "conf", "ini", "def", "list", "in"],
+    ),
+    ("text/markdown", &["md", "markdown"]),
+    ("text/csv", &["csv"]),
+    ("text/tab-separated-values", &["tsv"]),
+    ("text/calendar", &["ics", "ifb"]),
+    ("text/vcard", &["vcf", "vcard"]),
+    ("text/vtt", &["vtt"]),
+    ("text/x-rust", &["rs"]),
+    ("text/x-python", &["py"]),
+    ("text/x-c", &["c", "h"]),
+    ("text/x-c++", &["cpp", "cc", "cxx", "hpp"]),
+    ("text/x-java", &["java"]),
+    // `.ts` is more likely typescript than an MPEG transport stream
+    ("text/x-typescript", &["ts", "mts", "cts"]),
+    ("text/x-shellscript", &["sh"]),
+    ("text/x-nushell", &["nu"]),
+    ("text/cache-manifest", &["appcache", "manifest"]),
+    // application
+    ("application/json", &["json", "map"]),
+    ("application/ld+json", &["jsonld"]),
+    ("application/manifest+json", &["webmanifest"]),
+    ("application/geo+json", &["geojson"]),
+    ("application/xml", &["xml", "xsl", "xsd"]),
+    ("text/xml", &["xml"]),
+    ("application/xhtml+xml", &["xhtml", "xht"]),
+    ("application/atom+xml", &["atom"]),
+    ("application/rss+xml", &["rss"]),
+    ("application/toml", &["toml"]),
+    ("application/yaml", &["yaml", "yml"]),
+    ("application/wasm", &["wasm"]),
+    ("application/pdf", &["pdf"]),
+    ("application/postscript", &["ps", "eps", "ai"]),
+    ("application/rtf", &["rtf"]),
+    ("application/zip", &["zip"]),
+    ("application/gzip", &["gz", "tgz"]),
+    ("application/x-bzip2", &["bz2"]),
+    ("application/x-xz", &["xz"]),
+    ("application/zstd", &["zst"]),
+    ("application/x-tar", &["tar"]),
+    ("application/x-7z-compressed", &["7z"]),
+    ("application/vnd.rar", &["rar"]),
+    ("application/java-archive", &["jar"]),
+    ("application/epub+zip", &["epub"]),
+    ("application/msword", &["doc", "dot"]),
+    (
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        &["docx"],
+    ),
+    ("application/vnd.ms-excel", &["xls"]),
+    (
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        &["xlsx"],
+    ),
+    ("application/vnd.ms-powerpoint", &["ppt"]),
+    (
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        &["pptx"],
+    ),
+    ("application/vnd.oasis.opendocument.text", &["odt"]),
+    ("application/vnd.oasis.opendocument.spreadsheet", &["ods"]),
+    ("application/vnd.oasis.opendocument.presentation", &["odp"]),
+    ("application/sql", &["sql"]),
+    ("application/x-sqlite3", &["sqlite", "db"]),
+    ("application/vnd.apache.parquet", &["parquet"]),
+    ("application/msgpack", &["msgpack"]),
+    ("application/x-shockwave-flash", &["swf"]),
+    ("application/x-msdownload", &["exe", "dll"]),
+    ("application/vnd.debian.binary-package", &["deb"]),
+    ("application/x-rpm", &["rpm"]),
+    ("application/x-apple-diskimage", &["dmg"]),
+    ("application/x-iso9660-image", &["iso"]),
+    ("application/ogg", &["ogx"]),
+    ("application/mbox", &["mbox"]),
+    ("application/pgp-signature", &["sig", "asc"]),
+    ("application/octet-stream", &["bin"]),
+    // images
+    ("image/png", &["png"]),
+    ("image/jpeg", &["jpg", "jpeg", "jpe", "jfif"]),
+    ("image/gif", &["gif"]),
+    ("image/webp", &["webp"]),
+    ("image/avif", &["avif"]),
+    ("image/svg+xml", &["svg", "svgz"]),
+    ("image/bmp", &["bmp"]),
+    ("image/x-icon", &["ico", "cur"]),
+    ("image/tiff", &["tif", "tiff"]),
+    ("image/heic", &["heic"]),
+    ("image/heif", &["heif"]),
+    ("image/jxl", &["jxl"]),
+    ("image/apng", &["apng"]),
+    // audio
+    ("audio/mpeg", &["mp3", "mpga"]),
+    ("audio/ogg", &["ogg", "oga", "opus"]),
+    ("audio/wav", &["wav"]),
+    ("audio/flac", &["flac"]),
+    ("audio/aac", &["aac"]),
+    ("audio/mp4", &["m4a"]),
+    ("audio/webm", &["weba"]),
+    ("audio/midi", &["mid", "midi"]),
+    ("audio/aiff", &["aif", "aiff"]),
+    // video
+    ("video/mp4", &["mp4", "m4v"]),
+    ("video/webm", &["webm"]),
+    ("video/ogg", &["ogv"]),
+    ("video/quicktime", &["mov", "qt"]),
+    ("video/x-matroska", &["mkv"]),
+    ("video/x-msvideo", &["avi"]),
+    ("video/mpeg", &["mpeg", "mpg"]),
+    ("video/mp2t", &["ts"]),
+    ("video/3gpp", &["3gp"]),
+    // fonts
+    ("font/woff2", &["woff2"]),
+    ("font/woff", &["woff"]),
+    ("font/ttf", &["ttf"]),
+    ("font/otf", &["otf"]),
+    ("font/collection", &["ttc"]),
+    ("application/vnd.ms-fontobject", &["eot"]),
+];
+
+/// All mime types using an extension (without the dot, case-insensitive), most likely first.
+pub fn mime_types_from_extension(extension: &str) -> Vec<&'static str> {
+    let extension = extension.to_ascii_lowercase();
+    TABLE
+        .iter()
+        .filter(|(_, extensions)| extensions.contains(&extension.as_str()))
+        .map(|(mime, _)| *mime)
+        .collect()
+}
+
+/// The most likely mime type of a file.
+pub fn mime_type_from_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?;
+    mime_types_from_extension(extension).first().copied()
+}
+
+/// The extensions of a mime type (`type/subtype`, lowercase), usual one first.
+pub fn extensions(essence: &str) -> &'static [&'static str] {
+    TABLE
+        .iter()
+        .find(|(mime, _)| *mime == essence)
+        .map_or(&[], |(_, extensions)| extensions)
+}
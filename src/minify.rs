@@ -0,0 +1,602 @@
+use ego_tree::NodeRef;
+use html5ever::ns;
+use scraper::Node;
+use scraper::node::Element;
+
+use crate::format_qual_name;
+use crate::serialize::{
+    RAW_TEXT_ELEMENTS, Syntax, VOID_ELEMENTS, escape, is_html_element, preserves_whitespace,
+};
+
+pub struct MinifyOptions {
+    /// keep `<!--[if IE]>...<![endif]-->`
+    pub keep_conditional_comments: bool,
+    /// minify the content of `<style>` and `<script>`
+    pub minify_inline: bool,
+}
+
+/// Minifies the children of `node` (usually the document node).
+pub fn minify_children(node: NodeRef<'_, Node>, options: &MinifyOptions) -> String {
+    let mut out = String::new();
+    write_children(node, options, false, &mut out);
+    out
+}
+
+/// `</p>` can be omitted if the `<p>` is followed by one of these.
+const P_CLOSING_ELEMENTS: [&str; 32] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "search",
+    "section",
+    "table",
+    "ul",
+];
+
+const BOOLEAN_ATTRIBUTES: [&str; 27] = [
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+    "shadowrootclonable",
+    "shadowrootdelegatesfocus",
+];
+
+/// Elements which are displayed as blocks (whitespace next to them isn't rendered).
+/// Other elements (including custom elements) are inline.
+const BLOCK_ELEMENTS: [&str; 22] = [
+    "body", "caption", "center", "col", "colgroup", "dd", "dt", "frameset", "head", "html",
+    "legend", "li", "optgroup", "option", "summary", "tbody", "td", "tfoot", "th", "thead",
+    "title", "tr",
+];
+
+fn is_block(node: NodeRef<'_, Node>) -> bool {
+    match node.value() {
+        Node::Element(element) => {
+            is_html_element(element, &BLOCK_ELEMENTS)
+                || is_html_element(element, &P_CLOSING_ELEMENTS)
+        }
+        Node::Document | Node::Fragment => true,
+        _ => false,
+    }
+}
+
+fn is_conditional_comment(comment: &str) -> bool {
+    comment.starts_with("[if ") || comment.starts_with("<![endif]")
+}
+
+/// A child which is kept, with its minified text.
+struct Item<'a> {
+    node: NodeRef<'a, Node>,
+    text: Option<String>,
+}
+
+fn write_children(
+    parent: NodeRef<'_, Node>,
+    options: &MinifyOptions,
+    preserve: bool,
+    out: &mut String,
+) {
+    let children: Vec<NodeRef<'_, Node>> = parent
+        .children()
+        .filter(|child| match child.value() {
+            Node::Comment(comment) => {
+                options.keep_conditional_comments && is_conditional_comment(comment)
+            }
+            _ => true,
+        })
+        .collect();
+
+    // whitespace at the start/end of a block and next to block-level elements is insignificant,
+    // in <html> and <head> it is never rendered (but the <html> of a fragment is only its container)
+    let is_fragment_root = parent
+        .parent()
+        .is_some_and(|p| matches!(p.value(), Node::Fragment));
+    let invisible = !is_fragment_root
+        && parent
+            .value()
+            .as_element()
+            .is_some_and(|p| is_html_element(p, &["html", "head"]));
+    let mut items: Vec<Item<'_>> = Vec::new();
+    for (i, child) in children.iter().enumerate() {
+        let Node::Text(text) = child.value() else {
+            items.push(Item {
+                node: *child,
+                text: None,
+            });
+            continue;
+        };
+        if preserve {
+            items.push(Item {
+                node: *child,
+                text: Some(text.to_string()),
+            });
+            continue;
+        }
+        let mut collapsed = String::new();
+        for (j, word) in text.split_ascii_whitespace().enumerate() {
+            if j > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        let starts_with_space = text.starts_with(|c: char| c.is_ascii_whitespace());
+        let ends_with_space = text.ends_with(|c: char| c.is_ascii_whitespace());
+        let block_before = invisible
+            || match items.last() {
+                Some(item) => is_block(item.node),
+                None => is_block(parent),
+            };
+        let block_after = invisible
+            || match children.get(i + 1) {
+                Some(next) => is_block(*next),
+                None => is_block(parent),
+            };
+        let previous_space = items
+            .last()
+            .and_then(|item| item.text.as_ref())
+            .is_some_and(|t| t.ends_with(' '));
+        if starts_with_space && !block_before && !previous_space && !collapsed.is_empty() {
+            collapsed.insert(0, ' ');
+        }
+        if ends_with_space && !block_after {
+            if collapsed.is_empty() && (block_before || previous_space) {
+                continue;
+            }
+            collapsed.push(' ');
+        }
+        if !collapsed.is_empty() {
+            items.push(Item {
+                node: *child,
+                text: Some(collapsed),
+            });
+        }
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        let next = items.get(i + 1);
+        match item.node.value() {
+            Node::Text(_) => {
+                let text = item.text.as_deref().unwrap_or_default();
+                if parent
+                    .value()
+                    .as_element()
+                    .is_some_and(|p| is_html_element(p, &RAW_TEXT_ELEMENTS))
+                {
+                    out.push_str(&minify_raw_text(parent, text, options));
+                } else {
+                    out.push_str(&escape(text, false, Syntax::Html));
+                }
+            }
+            Node::Element(element) => {
+                write_element(item.node, element, parent, next, options, preserve, out)
+            }
+            Node::Comment(comment) => {
+                out.push_str("<!--");
+                out.push_str(comment);
+                out.push_str("-->");
+            }
+            Node::Doctype(doctype) => {
+                out.push_str("<!doctype ");
+                out.push_str(&doctype.name);
+                // the ids influence the quirks mode
+                if !doctype.public_id.is_empty() {
+                    out.push_str(&format!(" PUBLIC \"{}\"", doctype.public_id));
+                } else if !doctype.system_id.is_empty() {
+                    out.push_str(" SYSTEM");
+                }
+                if !doctype.system_id.is_empty() {
+                    out.push_str(&format!(" \"{}\"", doctype.system_id));
+                }
+                out.push('>');
+            }
+            // not created by the html parser
+            Node::ProcessingInstruction(_) => {}
+            Node::Document | Node::Fragment => write_children(item.node, options, preserve, out),
+        }
+    }
+}
+
+fn write_element(
+    node: NodeRef<'_, Node>,
+    element: &Element,
+    parent: NodeRef<'_, Node>,
+    next: Option<&Item<'_>>,
+    options: &MinifyOptions,
+    preserve: bool,
+    out: &mut String,
+) {
+    let is_html = element.name.ns == ns!(html);
+    let name = format_qual_name(&element.name);
+    if !can_omit_start_tag(node, element) {
+        out.push('<');
+        out.push_str(&name);
+        write_attributes(element, out);
+        out.push('>');
+    }
+    if is_html && VOID_ELEMENTS.contains(&&*name) {
+        return;
+    }
+    // the html parser drops the first newline of these elements
+    if is_html_element(element, &["pre", "textarea", "listing"])
+        && node
+            .first_child()
+            .and_then(|c| c.value().as_text().map(|t| t.starts_with('\n')))
+            .unwrap_or(false)
+    {
+        out.push('\n');
+    }
+    write_children(
+        node,
+        options,
+        preserve || preserves_whitespace(element, Syntax::Html),
+        out,
+    );
+    if !is_html || !can_omit_end_tag(&name, parent, next.map(|n| n.node)) {
+        out.push_str("</");
+        out.push_str(&name);
+        out.push('>');
+    }
+}
+
+fn write_attributes(element: &Element, out: &mut String) {
+    for (name, value) in element.attrs.iter() {
+        let name = format_qual_name(name);
+        out.push(' ');
+        out.push_str(&name);
+        // `checked="checked"`, but not `hidden="until-found"`
+        if value.is_empty()
+            || (element.name.ns == ns!(html)
+                && BOOLEAN_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str())
+                && value.eq_ignore_ascii_case(&name))
+        {
+            continue;
+        }
+        out.push('=');
+        let needs_quotes = value
+            .chars()
+            .any(|c| c.is_ascii_whitespace() || matches!(c, '"' | '\'' | '=' | '<' | '>' | '`'));
+        if !needs_quotes {
+            out.push_str(&value.replace('&', "&amp;"));
+        } else if value.contains('"') && !value.contains('\'') {
+            out.push('\'');
+            out.push_str(&value.replace('&', "&amp;"));
+            out.push('\'');
+        } else {
+            out.push('"');
+            out.push_str(&escape(value, true, Syntax::Html));
+            out.push('"');
+        }
+    }
+}
+
+fn can_omit_start_tag(node: NodeRef<'_, Node>, element: &Element) -> bool {
+    if element.name.ns != ns!(html) || !element.attrs.is_empty() {
+        return false;
+    }
+    let first_child = node.first_child().map(|c| c.value());
+    match &*element.name.local {
+        "html" => !matches!(first_child, Some(Node::Comment(_))),
+        "head" => matches!(first_child, None | Some(Node::Element(_))),
+        "body" => match first_child {
+            None => true,
+            Some(Node::Text(text)) => !text.starts_with(|c: char| c.is_ascii_whitespace()),
+            Some(Node::Element(child)) => !is_html_element(
+                child,
+                &["meta", "noscript", "link", "script", "style", "template"],
+            ),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The rules of https://html.spec.whatwg.org/multipage/syntax.html#optional-tags
+fn can_omit_end_tag(
+    name: &str,
+    parent: NodeRef<'_, Node>,
+    next: Option<NodeRef<'_, Node>>,
+) -> bool {
+    let next_is = |names: &[&str]| {
+        next.and_then(|n| n.value().as_element())
+            .is_some_and(|n| is_html_element(n, names))
+    };
+    let next_is_element_or_end = next.is_none_or(|n| n.value().is_element());
+    match name {
+        "html" | "body" => next.is_none_or(|n| !n.value().is_comment()),
+        "head" | "colgroup" | "caption" => next_is_element_or_end,
+        "li" => next.is_none() || next_is(&["li"]),
+        "dt" => next_is(&["dt", "dd"]),
+        "dd" => next.is_none() || next_is(&["dt", "dd"]),
+        "rt" | "rp" => next.is_none() || next_is(&["rt", "rp"]),
+        "optgroup" => next.is_none() || next_is(&["optgroup"]),
+        "option" => next.is_none() || next_is(&["option", "optgroup"]),
+        "thead" => next_is(&["tbody", "tfoot"]),
+        "tbody" => next.is_none() || next_is(&["tbody", "tfoot"]),
+        "tfoot" => next.is_none(),
+        "tr" => next.is_none() || next_is(&["tr"]),
+        "td" | "th" => next.is_none() || next_is(&["td", "th"]),
+        "p" => {
+            next_is(&P_CLOSING_ELEMENTS)
+                || (next.is_none()
+                    && !parent.value().as_element().is_some_and(|p| {
+                        is_html_element(
+                            p,
+                            &["a", "audio", "del", "ins", "map", "noscript", "video"],
+                        ) || p.name.local.contains('-')
+                    }))
+        }
+        _ => false,
+    }
+}
+
+fn minify_raw_text(parent: NodeRef<'_, Node>, text: &str, options: &MinifyOptions) -> String {
+    let Some(element) = parent.value().as_element() else {
+        return text.to_owned();
+    };
+    match &*element.name.local {
+        #[cfg(feature = "css")]
+        "style" if options.minify_inline => crate::css::minify::minify_css(text),
+        "script" if options.minify_inline && is_script_type(element.attr("type")) => {
+            minify_js(text)
+        }
+        _ => text.to_owned(),
+    }
+}
+
+/// JavaScript and JSON (which is a subset).
+fn is_script_type(script_type: Option<&str>) -> bool {
+    let Some(script_type) = script_type else {
+        return true;
+    };
+    let script_type = script_type.trim().to_ascii_lowercase();
+    script_type.is_empty()
+        || script_type == "module"
+        || script_type.contains("javascript")
+        || script_type.contains("ecmascript")
+        || script_type.contains("json")
+}
+
+/// A conservative JavaScript minifier: removes comments and whitespace, but keeps line breaks
+/// where automatic semicolon insertion could depend on them.
+pub fn minify_js(js: &str) -> String {
+    let chars: Vec<char> = js.chars().collect();
+    let mut out = String::with_capacity(js.len());
+    // whitespace (and comments) since the last token: `None`, `Some(false)` or `Some(true)` (with newline)
+    let mut whitespace: Option<bool> = None;
+    // open `${` of template literals (to find the end of the substitution)
+    let mut template_depth: Vec<usize> = Vec::new();
+    let mut braces: usize = 0;
+    // open parentheses: whether they belong to `if`, `while`, `for` or `with`
+    let mut parentheses: Vec<bool> = Vec::new();
+    // whether the last `)` ended the condition of a statement (`if (x) /re/.test(s)`)
+    let mut after_condition = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            whitespace = Some(whitespace == Some(true) || c == '\n' || c == '\r');
+            i += 1;
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            whitespace = Some(whitespace.unwrap_or(false));
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start = i;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            let newline = chars[start..i.min(chars.len())].contains(&'\n');
+            whitespace = Some(whitespace == Some(true) || newline);
+            continue;
+        }
+
+        if let Some(newline) = whitespace.take() {
+            let previous = out.chars().last();
+            if newline {
+                if previous.is_some_and(|p| !"{;,([".contains(p)) && !",;)]}".contains(c) {
+                    out.push('\n');
+                }
+            } else if previous.is_some_and(|p| {
+                (is_identifier_char(p) && is_identifier_char(c)) || (p == c && "+-/".contains(c))
+            }) || (c == '.' && ends_with_integer(&out))
+            {
+                out.push(' ');
+            }
+        }
+
+        let start = i;
+        match c {
+            '\'' | '"' => i = skip_string(&chars, i, c),
+            '`' => i = skip_template(&chars, i + 1),
+            '}' if template_depth.last() == Some(&braces) => {
+                // end of a `${}` substitution, continue with the template literal
+                template_depth.pop();
+                i = skip_template(&chars, i + 1);
+            }
+            '/' if regex_allowed(&out, after_condition) => i = skip_regex(&chars, i),
+            _ => {
+                match c {
+                    '{' => braces += 1,
+                    '}' => braces = braces.saturating_sub(1),
+                    '(' => parentheses.push(matches!(
+                        last_word(&out).as_str(),
+                        "if" | "while" | "for" | "with"
+                    )),
+                    ')' => after_condition = parentheses.pop().unwrap_or(false),
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+        out.extend(&chars[start..i.min(chars.len())]);
+        if out.ends_with("${") && matches!(chars.get(start), Some('`' | '}')) {
+            template_depth.push(braces);
+        }
+    }
+    out
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '\\' || !c.is_ascii()
+}
+
+/// Returns the index after the closing quote.
+fn skip_string(chars: &[char], start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote || c == '\n' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// Skips the content of a template literal, returns the index after the closing backtick
+/// or after the `${` of a substitution.
+fn skip_template(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '`' => return i + 1,
+            '$' if chars.get(i + 1) == Some(&'{') => return i + 2,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+fn skip_regex(chars: &[char], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut in_class = false;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '[' => {
+                in_class = true;
+                i += 1;
+            }
+            ']' => {
+                in_class = false;
+                i += 1;
+            }
+            '/' if !in_class => {
+                i += 1;
+                // flags
+                while i < chars.len() && chars[i].is_alphanumeric() {
+                    i += 1;
+                }
+                return i;
+            }
+            '\n' => return i,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// Whether a `/` starts a regular expression (instead of being a division).
+/// `after_condition`: the last `)` closed the condition of `if`, `while`, ...
+fn regex_allowed(out: &str, after_condition: bool) -> bool {
+    let Some(previous) = out.chars().last() else {
+        return true;
+    };
+    if "(,=:[!&|?{};+-*%<>~^".contains(previous) {
+        return true;
+    }
+    if previous == ')' {
+        return after_condition;
+    }
+    matches!(
+        last_word(out).as_str(),
+        "return"
+            | "typeof"
+            | "instanceof"
+            | "case"
+            | "do"
+            | "else"
+            | "in"
+            | "of"
+            | "new"
+            | "delete"
+            | "void"
+            | "throw"
+            | "yield"
+            | "await"
+    )
+}
+
+/// The identifier or keyword at the end of `out`.
+fn last_word(out: &str) -> String {
+    let mut word: Vec<char> = out
+        .chars()
+        .rev()
+        .take_while(|c| is_identifier_char(*c))
+        .collect();
+    word.reverse();
+    word.into_iter().collect()
+}
+
+/// Whether `out` ends with an integer literal (`1 .toString()` must not become `1.toString()`).
+fn ends_with_integer(out: &str) -> bool {
+    let word = last_word(out);
+    word.starts_with(|c: char| c.is_ascii_digit())
+        && word.chars().all(|c| c.is_ascii_digit() || c == '_')
+        && !out[..out.len() - word.len()].ends_with('.')
+}
@@ -0,0 +1,308 @@
+//! MIME multipart bodies ([RFC 2046](https://www.rfc-editor.org/rfc/rfc2046#section-5.1)),
+//! as used by `multipart/form-data` requests, emails and MHTML archives.
+
+use crate::codec::base64_decode_lenient;
+
+/// A part of a multipart body: its (unfolded) headers and the raw body.
+pub struct Part {
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    /// The value of the first header with this name (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+}
+
+pub fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Splits a multipart body into its parts. The preamble and epilogue are ignored,
+/// a missing close delimiter ends the last part at the end of the input.
+pub fn parse_multipart(input: &[u8], boundary: &str) -> Result<Vec<Part>, String> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    let Some(mut position) = find_delimiter(input, delimiter, 0) else {
+        return Err(format!(
+            "The boundary '{boundary}' doesn't occur in the input"
+        ));
+    };
+
+    let mut parts = Vec::new();
+    loop {
+        let mut start = position + delimiter.len();
+        if input[start..].starts_with(b"--") {
+            return Ok(parts);
+        }
+        // transport padding and the line break after the delimiter
+        while matches!(input.get(start), Some(b' ' | b'\t')) {
+            start += 1;
+        }
+        if input[start..].starts_with(b"\r\n") {
+            start += 2;
+        } else if input[start..].starts_with(b"\n") {
+            start += 1;
+        }
+
+        let next = find_delimiter(input, delimiter, start);
+        let end = match next {
+            // the line break before the delimiter belongs to it
+            Some(next) if next >= start + 2 && input[..next].ends_with(b"\r\n") => next - 2,
+            Some(next) if next > start => next - 1,
+            Some(next) => next,
+            None => input.len(),
+        };
+        parts.push(parse_part(&input[start..end.max(start)]));
+        match next {
+            Some(next) => position = next,
+            None => return Ok(parts),
+        }
+    }
+}
+
+/// The position of the next delimiter at the start of a line.
+fn find_delimiter(input: &[u8], delimiter: &[u8], from: usize) -> Option<usize> {
+    let mut from = from;
+    while from + delimiter.len() <= input.len() {
+        let i = from
+            + input[from..]
+                .windows(delimiter.len())
+                .position(|w| w == delimiter)?;
+        if (i == 0 || input[i - 1] == b'\n') && ends_delimiter(&input[i + delimiter.len()..]) {
+            return Some(i);
+        }
+        from = i + 1;
+    }
+    None
+}
+
+/// Whether the text after a delimiter completes it: `--` (the close delimiter)
+/// or transport padding up to the end of the line (`--ab` is not a delimiter of `--a`).
+fn ends_delimiter(rest: &[u8]) -> bool {
+    if rest.starts_with(b"--") {
+        return true;
+    }
+    let padding = rest
+        .iter()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count();
+    matches!(&rest[padding..], [] | [b'\n', ..] | [b'\r', b'\n', ..])
+}
+
+fn parse_part(part: &[u8]) -> Part {
+    let (head, body) = split_head(part);
+    Part {
+        headers: parse_headers(head),
+        body: body.to_vec(),
+    }
+}
+
+/// Splits a message at the first empty line.
+fn split_head(message: &[u8]) -> (&[u8], &[u8]) {
+    if let Some(body) = message.strip_prefix(b"\r\n") {
+        return (b"", body);
+    }
+    if let Some(body) = message.strip_prefix(b"\n") {
+        return (b"", body);
+    }
+    for (i, w) in message.windows(2).enumerate() {
+        if w == b"\n\n" {
+            return (&message[..i + 1], &message[i + 2..]);
+        }
+        if w == b"\n\r" && message.get(i + 2) == Some(&b'\n') {
+            return (&message[..i + 1], &message[i + 3..]);
+        }
+    }
+    (message, b"")
+}
+
+/// Parses header lines, joining folded (indented) lines.
+pub fn parse_headers(head: &[u8]) -> Vec<(String, String)> {
+    let head = String::from_utf8_lossy(head);
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+    headers
+}
+
+/// Splits a message (such as an email or MHTML archive) into its headers and body.
+pub fn parse_message(message: &[u8]) -> Part {
+    parse_part(message)
+}
+
+/// Decodes a body according to its `Content-Transfer-Encoding` (`base64` or `quoted-printable`,
+/// others are returned as they are).
+pub fn decode_transfer_encoding(body: &[u8], encoding: Option<&str>) -> Vec<u8> {
+    match encoding.map(|e| e.trim().to_ascii_lowercase()).as_deref() {
+        Some("base64") => base64_decode_lenient(body),
+        Some("quoted-printable") => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    }
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc2045#section-6.7>
+fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] != b'=' {
+            out.push(input[i]);
+            i += 1;
+            continue;
+        }
+        let rest = &input[i + 1..];
+        if rest.starts_with(b"\r\n") {
+            // soft line break
+            i += 3;
+        } else if rest.starts_with(b"\n") {
+            i += 2;
+        } else if let Some(byte) = rest
+            .get(..2)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Serializes parts with CRLF line breaks. Fails if the boundary occurs in a body.
+pub fn serialize_multipart(parts: &[Part], boundary: &str) -> Result<Vec<u8>, String> {
+    let delimiter = format!("--{boundary}");
+    let mut out = Vec::new();
+    for part in parts {
+        if part
+            .body
+            .windows(delimiter.len())
+            .any(|w| w == delimiter.as_bytes())
+        {
+            return Err(format!("The boundary '{boundary}' occurs in a body"));
+        }
+        out.extend_from_slice(delimiter.as_bytes());
+        out.extend_from_slice(b"\r\n");
+        for (name, value) in &part.headers {
+            out.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+        }
+        out.extend_from_slice(b"\r\n");
+        out.extend_from_slice(&part.body);
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(delimiter.as_bytes());
+    out.extend_from_slice(b"--\r\n");
+    Ok(out)
+}
+
+/// A `Content-Disposition` header: `form-data; name="field"; filename="a.txt"`.
+pub struct Disposition {
+    /// lowercase (`form-data`, `attachment`, `inline`)
+    pub kind: String,
+    pub name: Option<String>,
+    pub filename: Option<String>,
+}
+
+pub fn parse_disposition(value: &str) -> Disposition {
+    let (kind, parameters) = parse_parameters(value);
+    let parameter = |name: &str| {
+        parameters
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    };
+    Disposition {
+        kind: kind.to_ascii_lowercase(),
+        name: parameter("name"),
+        // filename* (RFC 6266) is preferred if it can be decoded
+        filename: parameter("filename*")
+            .and_then(|v| decode_extended_value(&v))
+            .or_else(|| parameter("filename")),
+    }
+}
+
+/// Splits `value; a=b; c="d"` into `value` and unquoted parameters.
+fn parse_parameters(value: &str) -> (String, Vec<(String, String)>) {
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            segment.push(c);
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => segments.push(std::mem::take(&mut segment)),
+            _ => segment.push(c),
+        }
+    }
+    segments.push(segment);
+
+    let mut segments = segments.into_iter();
+    let first = segments.next().unwrap_or_default().trim().to_owned();
+    let parameters = segments
+        .filter_map(|s| {
+            let (name, value) = s.split_once('=')?;
+            Some((name.trim().to_owned(), value.trim().to_owned()))
+        })
+        .collect();
+    (first, parameters)
+}
+
+/// `UTF-8''n%C3%A4me.txt` (<https://www.rfc-editor.org/rfc/rfc8187>)
+fn decode_extended_value(value: &str) -> Option<String> {
+    let (charset, rest) = value.split_once('\'')?;
+    let (_language, encoded) = rest.split_once('\'')?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        match (b, tail) {
+            (b'%', [h, l, ..]) => {
+                let hex = std::str::from_utf8(&[*h, *l]).ok()?.to_owned();
+                bytes.push(u8::from_str_radix(&hex, 16).ok()?);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else {
+        // iso-8859-1
+        Some(bytes.into_iter().map(char::from).collect())
+    }
+}
+
+/// Quotes a `Content-Disposition` parameter value the way browsers do for form submissions.
+pub fn quote_disposition_value(value: &str) -> String {
+    let escaped = value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A");
+    format!("\"{escaped}\"")
+}
@@ -12,6 +12,29 @@ impl nu_plugin::Plugin for NuPluginServo {
             // +------+
             Box::new(crate::commands::parse_html::ParseHtmlCommand),
             Box::new(crate::commands::query_html::QueryHtmlCommand),
+            #[cfg(feature = "render")]
+            Box::new(crate::commands::render_html::RenderHtmlCommand),
+            #[cfg(all(feature = "css", feature = "scraper_backend"))]
+            Box::new(crate::commands::inline_styles::InlineStylesCommand),
+            #[cfg(all(
+                feature = "css",
+                feature = "data_url",
+                feature = "mime",
+                feature = "scraper_backend"
+            ))]
+            Box::new(crate::commands::inline_resources::InlineResourcesCommand),
+            #[cfg(all(feature = "css", feature = "data_url", feature = "scraper_backend"))]
+            Box::new(crate::commands::data_urls::DataUrlsCommand),
+            #[cfg(all(feature = "css", feature = "scraper_backend"))]
+            Box::new(crate::commands::sanitize_html::SanitizeHtmlCommand),
+            #[cfg(feature = "scraper_backend")]
+            Box::new(crate::commands::update_html::UpdateHtmlCommand),
+            #[cfg(feature = "scraper_backend")]
+            Box::new(crate::commands::format_html::FormatHtmlCommand),
+            #[cfg(feature = "scraper_backend")]
+            Box::new(crate::commands::minify_html::MinifyHtmlCommand),
+            #[cfg(feature = "scraper_backend")]
+            Box::new(crate::commands::diff_html::DiffHtmlCommand),
             // +-----+
             // | XML |
             // +-----+
@@ -19,16 +42,75 @@ impl nu_plugin::Plugin for NuPluginServo {
             Box::new(crate::commands::parse_xml::ParseXmlCommand),
             #[cfg(feature = "xml")]
             Box::new(crate::commands::query_xml::QueryXmlCommand),
+            #[cfg(feature = "xml")]
+            Box::new(crate::commands::format_xml::FormatXmlCommand),
+            #[cfg(feature = "xml")]
+            Box::new(crate::commands::diff_xml::DiffXmlCommand),
             // +-----+
             // | URL |
             // +-----+
+            #[cfg(feature = "url")]
+            Box::new(crate::commands::parse_url::ParseUrlCommand),
+            #[cfg(feature = "url")]
+            Box::new(crate::commands::join_url::JoinUrlCommand),
+            #[cfg(feature = "url")]
+            Box::new(crate::commands::to_url::ToUrlCommand),
             #[cfg(feature = "data_url")]
             Box::new(crate::commands::parse_data_url::ParseDataUrlCommand),
+            #[cfg(feature = "data_url")]
+            Box::new(crate::commands::to_data_url::ToDataUrlCommand),
+            // +-----+
+            // | CSS |
+            // +-----+
+            #[cfg(feature = "css")]
+            Box::new(crate::commands::parse_css::ParseCssCommand),
+            #[cfg(feature = "css")]
+            Box::new(crate::commands::css_selector::CssSelectorCommand),
+            #[cfg(feature = "css")]
+            Box::new(crate::commands::css_coverage::CssCoverageCommand),
+            #[cfg(feature = "css")]
+            Box::new(crate::commands::css_declarations::CssDeclarationsCommand),
+            #[cfg(feature = "css")]
+            Box::new(crate::commands::css_value::CssValueCommand),
             // +------+
             // | MIME |
             // +------+
             #[cfg(feature = "mime")]
             Box::new(crate::commands::mime::ParseMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::sniff_mime::SniffMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::to_mime::ToMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::matches_mime::MatchesMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::negotiate_mime::NegotiateMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::from_path_mime::FromPathMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::extensions_mime::ExtensionsMimeCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::parse_multipart::ParseMultipartCommand),
+            #[cfg(feature = "mime")]
+            Box::new(crate::commands::to_multipart::ToMultipartCommand),
+            // +-------+
+            // | MHTML |
+            // +-------+
+            #[cfg(all(
+                feature = "css",
+                feature = "data_url",
+                feature = "mime",
+                feature = "scraper_backend",
+                feature = "url"
+            ))]
+            Box::new(crate::commands::parse_mhtml::ParseMhtmlCommand),
+            // +----------+
+            // | ENCODING |
+            // +----------+
+            #[cfg(feature = "encoding")]
+            Box::new(crate::commands::decode::DecodeCommand),
+            #[cfg(feature = "encoding")]
+            Box::new(crate::commands::encode::EncodeCommand),
         ]
     }
 }
@@ -1,13 +1,29 @@
+use ego_tree::NodeId;
 use html5ever::tendril::TendrilSink;
+use html5ever::{Attribute, LocalName, QualName, local_name, ns};
 use nu_protocol::{LabeledError, Record, Span, Value};
-use scraper::{ElementRef, Html, Selector};
+use scraper::{ElementRef, Html, Node, Selector};
 
-use crate::{HtmlBackend, format_qual_name};
+use crate::{HtmlBackend, HtmlBackendMut, InsertPosition, format_qual_name};
 
 #[derive(Copy, Clone)]
 pub struct ScraperBackend;
 
 impl ScraperBackend {
+    /// Like `parse`, but keeps the doctype (for commands which output the whole document again).
+    pub fn parse_with_doctype(&self, document: &Value) -> Result<Html, nu_protocol::LabeledError> {
+        parse_html(document, false)
+    }
+
+    /// Parses a fragment (in the context of `<body>`), `get_root_node` is the `<html>` wrapper.
+    pub fn parse_fragment(&self, fragment: &Value) -> Result<Html, nu_protocol::LabeledError> {
+        match fragment {
+            Value::String { val, .. } => Ok(Html::parse_fragment(val)),
+            Value::Binary { val, .. } => Ok(Html::parse_fragment(&String::from_utf8_lossy(val))),
+            _ => Err(LabeledError::new("Input type neither string nor binary")),
+        }
+    }
+
     #[cfg(feature = "xml")]
     pub fn parse_xml(&self, document: &Value) -> Result<Html, nu_protocol::LabeledError> {
         let mut document: &[u8] = match document {
@@ -25,6 +41,123 @@ impl ScraperBackend {
         .read_from(&mut document)
         .map_err(|err| LabeledError::new(format!("Failed to parse html: {err}")))
     }
+
+    /// Like `css_query`, but namespace prefixes (`atom|entry`, `[xlink|href]`) can be used.
+    /// `namespaces` are `(prefix, uri)` pairs.
+    #[cfg(feature = "xml")]
+    pub fn css_query_with_namespaces<'a>(
+        &self,
+        html: &'a Html,
+        selector: &str,
+        namespaces: &[(String, String)],
+    ) -> Result<Vec<ElementRef<'a>>, LabeledError> {
+        use scraper_selectors::matching;
+        use scraper_selectors::parser::{ParseRelative, SelectorList, SelectorParseErrorKind};
+
+        let mut input = cssparser::ParserInput::new(selector);
+        let mut input = cssparser::Parser::new(&mut input);
+        let parser = NamespaceParser { namespaces };
+        let selectors =
+            SelectorList::parse(&parser, &mut input, ParseRelative::No).map_err(|err| {
+                if let cssparser::ParseErrorKind::Custom(
+                    SelectorParseErrorKind::ExpectedNamespace(prefix),
+                ) = &err.kind
+                {
+                    return LabeledError::new(format!("Unknown namespace prefix '{prefix}'"))
+                        .with_help(format!("declare it with --ns {prefix}=<uri>"));
+                }
+                LabeledError::new(format!(
+                    "Failed to parse CSS: {}",
+                    scraper::error::SelectorErrorKind::from(err)
+                ))
+            })?;
+
+        let mut caches = matching::SelectorCaches::default();
+        Ok(html
+            .tree
+            .nodes()
+            .filter_map(ElementRef::wrap)
+            .filter(|element| {
+                let mut context = matching::MatchingContext::new(
+                    matching::MatchingMode::Normal,
+                    None,
+                    &mut caches,
+                    matching::QuirksMode::NoQuirks,
+                    matching::NeedsSelectorFlags::No,
+                    matching::MatchingForInvalidation::No,
+                );
+                selectors
+                    .slice()
+                    .iter()
+                    .any(|s| matching::matches_selector(s, 0, None, element, &mut context))
+            })
+            .collect())
+    }
+}
+
+/// Parses selectors like `scraper::Selector`, resolving namespace prefixes.
+#[cfg(feature = "xml")]
+struct NamespaceParser<'n> {
+    namespaces: &'n [(String, String)],
+}
+
+#[cfg(feature = "xml")]
+impl NamespaceParser<'_> {
+    fn namespace(&self, prefix: &str) -> Option<html5ever::Namespace> {
+        self.namespaces
+            .iter()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, uri)| html5ever::Namespace::from(uri.as_str()))
+    }
+}
+
+#[cfg(feature = "xml")]
+impl<'i> scraper_selectors::parser::Parser<'i> for NamespaceParser<'_> {
+    type Impl = scraper::selector::Simple;
+    type Error = scraper_selectors::parser::SelectorParseErrorKind<'i>;
+
+    fn parse_is_and_where(&self) -> bool {
+        true
+    }
+
+    fn parse_has(&self) -> bool {
+        true
+    }
+
+    /// `--ns =uri`: the namespace of type selectors without prefix
+    fn default_namespace(&self) -> Option<html5ever::Namespace> {
+        self.namespace("")
+    }
+
+    fn namespace_for_prefix(
+        &self,
+        prefix: &scraper::selector::CssLocalName,
+    ) -> Option<html5ever::Namespace> {
+        self.namespace(&prefix.0).filter(|_| !prefix.0.is_empty())
+    }
+}
+
+fn parse_html(document: &Value, drop_doctype: bool) -> Result<Html, nu_protocol::LabeledError> {
+    let mut document: &[u8] = match document {
+        Value::String { val, .. } => val.as_bytes(),
+        Value::Binary { val, .. } => val,
+        _ => {
+            return Err(LabeledError::new("Input type neither string nor binary"));
+        }
+    };
+    html5ever::parse_document(
+        scraper::HtmlTreeSink::new(scraper::Html::new_document()),
+        html5ever::ParseOpts {
+            tree_builder: html5ever::tree_builder::TreeBuilderOpts {
+                drop_doctype,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .from_utf8()
+    .read_from(&mut document)
+    .map_err(|err| LabeledError::new(format!("Failed to parse html: {err}")))
 }
 
 impl HtmlBackend for ScraperBackend {
@@ -32,26 +165,7 @@ impl HtmlBackend for ScraperBackend {
     type Node<'a> = ElementRef<'a>;
 
     fn parse(&self, document: &Value) -> Result<Self::Document, nu_protocol::LabeledError> {
-        let mut document: &[u8] = match document {
-            Value::String { val, .. } => val.as_bytes(),
-            Value::Binary { val, .. } => val,
-            _ => {
-                return Err(LabeledError::new("Input type neither string nor binary"));
-            }
-        };
-        html5ever::parse_document(
-            scraper::HtmlTreeSink::new(scraper::Html::new_document()),
-            html5ever::ParseOpts {
-                tree_builder: html5ever::tree_builder::TreeBuilderOpts {
-                    drop_doctype: true,
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-        )
-        .from_utf8()
-        .read_from(&mut document)
-        .map_err(|err| LabeledError::new(format!("Failed to parse html: {err}")))
+        parse_html(document, true)
     }
 
     fn get_root_node<'a>(
@@ -104,6 +218,236 @@ impl HtmlBackend for ScraperBackend {
     }
 }
 
+impl HtmlBackendMut for ScraperBackend {
+    type NodeId = NodeId;
+
+    fn node_id(&self, node: Self::Node<'_>) -> Self::NodeId {
+        node.id()
+    }
+
+    fn is_attached(&self, html: &Html, node: NodeId) -> bool {
+        let root = html.tree.root().id();
+        html.tree
+            .get(node)
+            .is_some_and(|n| n.id() == root || n.ancestors().last().is_some_and(|a| a.id() == root))
+    }
+
+    fn get_attribute(&self, html: &Html, node: NodeId, name: &str) -> Option<String> {
+        ElementRef::wrap(html.tree.get(node)?)?
+            .attr(name)
+            .map(|v| v.to_owned())
+    }
+
+    fn set_attribute(
+        &self,
+        html: &mut Html,
+        node: NodeId,
+        name: &str,
+        value: &str,
+    ) -> Result<(), LabeledError> {
+        update_attributes(html, node, |attrs| {
+            match attrs
+                .iter_mut()
+                .find(|(n, _)| n.ns == ns!() && &*n.local == name)
+            {
+                Some((_, v)) => *v = value.into(),
+                None => attrs.push((
+                    QualName::new(None, ns!(), LocalName::from(name)),
+                    value.into(),
+                )),
+            }
+        })
+    }
+
+    fn remove_attribute(
+        &self,
+        html: &mut Html,
+        node: NodeId,
+        name: &str,
+    ) -> Result<(), LabeledError> {
+        update_attributes(html, node, |attrs| {
+            attrs.retain(|(n, _)| !(n.ns == ns!() && &*n.local == name))
+        })
+    }
+
+    fn set_inner_html(
+        &self,
+        html: &mut Html,
+        node: NodeId,
+        fragment: &str,
+    ) -> Result<(), LabeledError> {
+        let children = import_fragment(html, node, fragment);
+        let mut node = get_node_mut(html, node)?;
+        while let Some(mut child) = node.first_child() {
+            child.detach();
+        }
+        for child in children {
+            node.append_id(child);
+        }
+        Ok(())
+    }
+
+    fn set_text(&self, html: &mut Html, node: NodeId, text: &str) -> Result<(), LabeledError> {
+        let mut node = get_node_mut(html, node)?;
+        while let Some(mut child) = node.first_child() {
+            child.detach();
+        }
+        node.append(Node::Text(scraper::node::Text { text: text.into() }));
+        Ok(())
+    }
+
+    fn insert_html(
+        &self,
+        html: &mut Html,
+        node: NodeId,
+        position: InsertPosition,
+        fragment: &str,
+    ) -> Result<(), LabeledError> {
+        ensure_not_root(html, node, "insert next to")?;
+        let children = import_fragment(html, parent(html, node)?, fragment);
+        let mut node = get_node_mut(html, node)?;
+        match position {
+            InsertPosition::Before => {
+                for child in children {
+                    node.insert_id_before(child);
+                }
+            }
+            InsertPosition::After => {
+                for child in children.into_iter().rev() {
+                    node.insert_id_after(child);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn wrap(&self, html: &mut Html, node: NodeId, wrapper: &str) -> Result<(), LabeledError> {
+        ensure_not_root(html, node, "wrap")?;
+        let Some(outer) = import_fragment(html, parent(html, node)?, wrapper)
+            .into_iter()
+            .find(|id| html.tree.get(*id).is_some_and(|n| n.value().is_element()))
+        else {
+            return Err(LabeledError::new(format!(
+                "Wrapper '{wrapper}' does not contain an element"
+            )));
+        };
+        let mut inner = outer;
+        while let Some(child) = html
+            .tree
+            .get(inner)
+            .and_then(|n| n.children().find(|c| c.value().is_element()))
+        {
+            inner = child.id();
+        }
+        let mut node = get_node_mut(html, node)?;
+        node.insert_id_before(outer);
+        let node = node.id();
+        get_node_mut(html, inner)?.append_id(node);
+        Ok(())
+    }
+
+    fn unwrap(&self, html: &mut Html, node: NodeId) -> Result<(), LabeledError> {
+        ensure_not_root(html, node, "unwrap")?;
+        let mut node = get_node_mut(html, node)?;
+        while let Some(child) = node.first_child().map(|c| c.id()) {
+            node.insert_id_before(child);
+        }
+        node.detach();
+        Ok(())
+    }
+
+    fn remove(&self, html: &mut Html, node: NodeId) -> Result<(), LabeledError> {
+        ensure_not_root(html, node, "remove")?;
+        get_node_mut(html, node)?.detach();
+        Ok(())
+    }
+
+    fn document_html(&self, html: &Html) -> Result<String, LabeledError> {
+        Ok(html.html())
+    }
+}
+
+fn get_node_mut(
+    html: &mut Html,
+    node: NodeId,
+) -> Result<ego_tree::NodeMut<'_, Node>, LabeledError> {
+    html.tree
+        .get_mut(node)
+        .ok_or_else(|| LabeledError::new("Invalid node id"))
+}
+
+/// The document node and `<html>` can't be moved (scraper expects them to exist).
+fn ensure_not_root(html: &Html, node: NodeId, action: &str) -> Result<(), LabeledError> {
+    let root = html.tree.root();
+    if node == root.id()
+        || root
+            .children()
+            .any(|c| c.id() == node && c.value().is_element())
+    {
+        return Err(LabeledError::new(format!(
+            "Can't {action} the root element"
+        )));
+    }
+    Ok(())
+}
+
+fn parent(html: &Html, node: NodeId) -> Result<NodeId, LabeledError> {
+    html.tree
+        .get(node)
+        .and_then(|n| n.parent())
+        .map(|p| p.id())
+        .ok_or_else(|| LabeledError::new("Invalid node id"))
+}
+
+/// Parses `fragment` into (orphaned) nodes of `html` and returns their ids.
+/// The fragment is parsed as the content of `context` (`<td>`s are kept within a `<tr>`).
+fn import_fragment(html: &mut Html, context: NodeId, fragment: &str) -> Vec<NodeId> {
+    let context = match html.tree.get(context).map(|n| n.value()) {
+        Some(Node::Element(element)) => element.name.clone(),
+        _ => QualName::new(None, ns!(html), local_name!("body")),
+    };
+    let fragment = html5ever::driver::parse_fragment(
+        scraper::HtmlTreeSink::new(Html::new_fragment()),
+        Default::default(),
+        context,
+        Vec::new(),
+        false,
+    )
+    .one(fragment);
+    // the fragment tree is `Fragment > <html> > nodes`
+    let root = html.tree.extend_tree(fragment.tree).id();
+    html.tree
+        .get(root)
+        .and_then(|root| root.first_child())
+        .map(|wrapper| wrapper.children().map(|c| c.id()).collect())
+        .unwrap_or_default()
+}
+
+/// Changes the attributes of an element.
+/// The element is rebuilt since scraper caches its id and classes.
+fn update_attributes(
+    html: &mut Html,
+    node: NodeId,
+    update: impl FnOnce(&mut scraper::node::Attributes),
+) -> Result<(), LabeledError> {
+    let mut node = get_node_mut(html, node)?;
+    let Node::Element(element) = node.value() else {
+        return Err(LabeledError::new(
+            "Attributes can only be changed on elements",
+        ));
+    };
+    let mut attrs = element.attrs.clone();
+    update(&mut attrs);
+    *element = scraper::node::Element::new(
+        element.name.clone(),
+        attrs
+            .into_iter()
+            .map(|(name, value)| Attribute { name, value })
+            .collect(),
+    );
+    Ok(())
+}
+
 fn node2html_nu(span: Span, element: ElementRef<'_>) -> Value {
     let mut out = Record::new();
     out.push(
@@ -174,11 +518,30 @@ fn xml_element_to_nu(span: Span, element: ElementRef<'_>, text_as_elements: bool
         "tag",
         Value::string(format_qual_name(&element.value().name), span),
     );
+    // `from xml` doesn't have namespaces
+    if !text_as_elements {
+        let ns = &element.value().name.ns;
+        out.push(
+            "namespace",
+            if ns.is_empty() {
+                Value::nothing(span)
+            } else {
+                Value::string(ns.to_string(), span)
+            },
+        );
+    }
     let mut attributes = Record::new();
     for attr in element.value().attrs() {
         attributes.push(attr.0, Value::string(attr.1, span));
     }
     out.push("attributes", Value::record(attributes, span));
+    if !text_as_elements {
+        let mut namespaces = Record::new();
+        for (name, uri) in crate::serialize::namespaces_used(*element) {
+            namespaces.push(name, Value::string(uri, span));
+        }
+        out.push("namespaces_used", Value::record(namespaces, span));
+    }
     out.push(
         "content",
         Value::list(
@@ -0,0 +1,562 @@
+use ego_tree::NodeRef;
+use html5ever::{Namespace, Prefix, local_name, ns};
+use scraper::Node;
+use scraper::node::Element;
+
+use crate::format_qual_name;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Syntax {
+    Html,
+    Xml,
+}
+
+pub struct FormatOptions {
+    pub syntax: Syntax,
+    /// spaces per level
+    pub indent: usize,
+    /// start tags longer than this get one attribute per line
+    pub width: usize,
+    pub sort_attributes: bool,
+}
+
+/// Pretty-prints the children of `node` (usually the document node).
+/// Whitespace is only changed where it is insignificant: between block-level elements
+/// and at the start/end of their content.
+pub fn format_children(node: NodeRef<'_, Node>, options: &FormatOptions) -> String {
+    let mut formatter = Formatter {
+        options,
+        out: String::new(),
+    };
+    formatter.block_children(node, 0);
+    formatter.out.trim_end().to_owned()
+}
+
+pub(crate) const VOID_ELEMENTS: [&str; 16] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr", "basefont",
+];
+
+/// Elements whose text content is not escaped.
+pub(crate) const RAW_TEXT_ELEMENTS: [&str; 8] = [
+    "script",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "noscript",
+];
+
+/// Html elements which are formatted on lines of their own, all other elements
+/// (phrasing content, custom elements, inline svg and MathML) are part of a line of text.
+pub(crate) const BLOCK_ELEMENTS: [&str; 71] = [
+    "address",
+    "article",
+    "aside",
+    "base",
+    "blockquote",
+    "body",
+    "caption",
+    "center",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "legend",
+    "li",
+    "link",
+    "listing",
+    "main",
+    "menu",
+    "meta",
+    "nav",
+    "noembed",
+    "noframes",
+    "noscript",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "param",
+    "plaintext",
+    "pre",
+    "script",
+    "search",
+    "section",
+    "source",
+    "style",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "track",
+    "ul",
+    "xmp",
+];
+
+pub(crate) fn is_html_element(element: &Element, names: &[&str]) -> bool {
+    element.name.ns == ns!(html) && names.contains(&&*element.name.local)
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    out: String,
+}
+
+impl Formatter<'_> {
+    fn line(&mut self, depth: usize, content: &str) {
+        self.out.push_str(&" ".repeat(depth * self.options.indent));
+        self.out.push_str(content);
+        self.out.push('\n');
+    }
+
+    fn is_inline(&self, node: NodeRef<'_, Node>) -> bool {
+        match node.value() {
+            Node::Text(_) => true,
+            Node::Element(element) => {
+                self.options.syntax == Syntax::Html && !is_html_element(element, &BLOCK_ELEMENTS)
+            }
+            // within a line of text (`a<!--c-->b`) they are part of it
+            Node::Comment(_) | Node::ProcessingInstruction(_) => {
+                self.next_to_inline(node.prev_siblings())
+                    || self.next_to_inline(node.next_siblings())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the closest sibling with content (not whitespace, comments or PIs) is inline.
+    fn next_to_inline<'a>(&self, mut siblings: impl Iterator<Item = NodeRef<'a, Node>>) -> bool {
+        siblings
+            .find(|s| match s.value() {
+                Node::Comment(_) | Node::ProcessingInstruction(_) => false,
+                Node::Text(text) => !text.trim().is_empty(),
+                _ => true,
+            })
+            .is_some_and(|s| self.is_inline(s))
+    }
+
+    /// Writes the children of `node`, one block per line.
+    /// Consecutive inline nodes (text, `<b>`, ...) are written on a single line.
+    fn block_children(&mut self, node: NodeRef<'_, Node>, depth: usize) {
+        let mut run = String::new();
+        for child in node.children() {
+            if self.is_inline(child) {
+                write_compact(child, self.options, false, &mut run);
+                continue;
+            }
+            self.flush_run(&mut run, depth);
+            self.block(child, depth);
+        }
+        self.flush_run(&mut run, depth);
+    }
+
+    fn flush_run(&mut self, run: &mut String, depth: usize) {
+        let line = run.trim();
+        if !line.is_empty() {
+            let line = line.to_owned();
+            self.line(depth, &line);
+        }
+        run.clear();
+    }
+
+    fn block(&mut self, node: NodeRef<'_, Node>, depth: usize) {
+        let element = match node.value() {
+            Node::Document | Node::Fragment => {
+                self.block_children(node, depth);
+                return;
+            }
+            Node::Element(element) => element,
+            _ => {
+                let mut out = String::new();
+                write_compact(node, self.options, false, &mut out);
+                self.line(depth, &out);
+                return;
+            }
+        };
+        if preserves_whitespace(element, self.options.syntax) {
+            let mut out = String::new();
+            write_compact(node, self.options, true, &mut out);
+            self.line(depth, &out);
+            return;
+        }
+
+        let has_blocks = match self.options.syntax {
+            Syntax::Html => node
+                .children()
+                .any(|c| c.value().is_element() && !self.is_inline(c)),
+            // mixed content is kept on one line
+            Syntax::Xml => {
+                node.children().any(|c| c.value().is_element())
+                    && !node
+                        .children()
+                        .any(|c| c.value().as_text().is_some_and(|t| !t.trim().is_empty()))
+            }
+        };
+        let empty = !node.has_children();
+        let self_closing = empty
+            && match self.options.syntax {
+                Syntax::Html => is_html_element(element, &VOID_ELEMENTS),
+                Syntax::Xml => true,
+            };
+
+        let start_tag = start_tag(node, element, self.options, self_closing);
+        let wrap = depth * self.options.indent + start_tag.len() > self.options.width
+            && element.attrs.len() > 1;
+        if wrap {
+            self.line(depth, &format!("<{}", format_qual_name(&element.name)));
+            for attr in attributes(node, element, self.options) {
+                self.line(depth + 1, &attr);
+            }
+            if self_closing || has_blocks || self.options.syntax == Syntax::Html {
+                self.line(depth, if self_closing { "/>" } else { ">" });
+            }
+        }
+        if self_closing {
+            if !wrap {
+                self.line(depth, &start_tag);
+            }
+            return;
+        }
+        let end_tag = format!("</{}>", format_qual_name(&element.name));
+
+        if has_blocks {
+            if !wrap {
+                self.line(depth, &start_tag);
+            }
+            self.block_children(node, depth + 1);
+            self.line(depth, &end_tag);
+            return;
+        }
+
+        let mut content = String::new();
+        for child in node.children() {
+            write_compact(child, self.options, false, &mut content);
+        }
+        // in xml all text is significant, the content is kept as it is
+        if self.options.syntax == Syntax::Xml {
+            if wrap {
+                self.line(depth, &format!(">{content}{end_tag}"));
+            } else {
+                self.line(depth, &format!("{start_tag}{content}{end_tag}"));
+            }
+            return;
+        }
+        let content = content.trim();
+        if wrap {
+            if !content.is_empty() {
+                self.line(depth + 1, content);
+            }
+            self.line(depth, &end_tag);
+        } else {
+            self.line(depth, &format!("{start_tag}{content}{end_tag}"));
+        }
+    }
+}
+
+pub(crate) fn preserves_whitespace(element: &Element, syntax: Syntax) -> bool {
+    match syntax {
+        Syntax::Html => {
+            is_html_element(element, &["pre", "textarea", "listing"])
+                || is_html_element(element, &RAW_TEXT_ELEMENTS)
+        }
+        Syntax::Xml => element.attrs.iter().any(|(name, value)| {
+            name.ns == ns!(xml) && name.local == local_name!("space") && &**value == "preserve"
+        }),
+    }
+}
+
+fn attributes(node: NodeRef<'_, Node>, element: &Element, options: &FormatOptions) -> Vec<String> {
+    let declarations = match options.syntax {
+        Syntax::Html => Vec::new(),
+        Syntax::Xml => namespace_declarations(node),
+    };
+    let mut attrs: Vec<String> = declarations
+        .iter()
+        .map(|(name, uri)| (name.clone(), uri.as_str()))
+        .chain(
+            element
+                .attrs
+                .iter()
+                .map(|(name, value)| (format_qual_name(name), &**value)),
+        )
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value, true, options.syntax)))
+        .collect();
+    if options.sort_attributes {
+        attrs.sort();
+    }
+    attrs
+}
+
+fn start_tag(
+    node: NodeRef<'_, Node>,
+    element: &Element,
+    options: &FormatOptions,
+    self_closing: bool,
+) -> String {
+    let mut out = format!("<{}", format_qual_name(&element.name));
+    for attr in attributes(node, element, options) {
+        out.push(' ');
+        out.push_str(&attr);
+    }
+    out.push_str(match (self_closing, options.syntax) {
+        (true, Syntax::Xml) => "/>",
+        _ => ">",
+    });
+    out
+}
+
+/// Serializes a node as it is (without changing whitespace).
+pub fn serialize_node(node: NodeRef<'_, Node>, syntax: Syntax) -> String {
+    let options = FormatOptions {
+        syntax,
+        indent: 0,
+        width: usize::MAX,
+        sort_attributes: false,
+    };
+    let mut out = String::new();
+    write_compact(node, &options, true, &mut out);
+    out
+}
+
+/// Serializes a node without adding any whitespace.
+/// Unless `preserve` is set, whitespace in text is collapsed.
+fn write_compact(
+    node: NodeRef<'_, Node>,
+    options: &FormatOptions,
+    preserve: bool,
+    out: &mut String,
+) {
+    match node.value() {
+        Node::Document | Node::Fragment => {
+            for child in node.children() {
+                write_compact(child, options, preserve, out);
+            }
+        }
+        Node::Doctype(doctype) => {
+            out.push_str("<!DOCTYPE ");
+            out.push_str(&doctype.name);
+            if !doctype.public_id.is_empty() {
+                out.push_str(&format!(" PUBLIC \"{}\"", doctype.public_id));
+            } else if !doctype.system_id.is_empty() {
+                out.push_str(" SYSTEM");
+            }
+            if !doctype.system_id.is_empty() {
+                out.push_str(&format!(" \"{}\"", doctype.system_id));
+            }
+            out.push('>');
+        }
+        Node::Comment(comment) => {
+            out.push_str("<!--");
+            out.push_str(comment);
+            out.push_str("-->");
+        }
+        Node::ProcessingInstruction(pi) => {
+            out.push_str("<?");
+            out.push_str(&pi.target);
+            if !pi.data.is_empty() {
+                out.push(' ');
+                out.push_str(&pi.data);
+            }
+            out.push_str("?>");
+        }
+        Node::Text(text) => {
+            let raw = options.syntax == Syntax::Html
+                && node.parent().is_some_and(|p| {
+                    p.value()
+                        .as_element()
+                        .is_some_and(|p| is_html_element(p, &RAW_TEXT_ELEMENTS))
+                });
+            let text = if raw {
+                text.to_string()
+            } else {
+                escape(text, false, options.syntax)
+            };
+            if preserve {
+                out.push_str(&text);
+            } else {
+                collapse_whitespace(&text, out);
+            }
+        }
+        Node::Element(element) => {
+            let self_closing = !node.has_children()
+                && match options.syntax {
+                    Syntax::Html => is_html_element(element, &VOID_ELEMENTS),
+                    Syntax::Xml => true,
+                };
+            out.push_str(&start_tag(node, element, options, self_closing));
+            if self_closing {
+                return;
+            }
+            let preserve = preserve || preserves_whitespace(element, options.syntax);
+            // the html parser drops the first newline of these elements
+            if options.syntax == Syntax::Html
+                && is_html_element(element, &["pre", "textarea", "listing"])
+                && node
+                    .first_child()
+                    .and_then(|c| c.value().as_text().map(|t| t.starts_with('\n')))
+                    .unwrap_or(false)
+            {
+                out.push('\n');
+            }
+            for child in node.children() {
+                write_compact(child, options, preserve, out);
+            }
+            out.push_str(&format!("</{}>", format_qual_name(&element.name)));
+        }
+    }
+}
+
+/// The namespace declarations (`(xmlns:prefix, uri)`) an xml element needs.
+/// The xml parser resolves the prefixes and drops the declarations,
+/// so they are derived from the namespaces of the element and its ancestors.
+pub fn namespace_declarations(node: NodeRef<'_, Node>) -> Vec<(String, String)> {
+    let Some(element) = node.value().as_element() else {
+        return Vec::new();
+    };
+    let mut bindings: Vec<(Option<Prefix>, Namespace)> =
+        vec![(element.name.prefix.clone(), element.name.ns.clone())];
+    for (name, _) in element.attrs.iter() {
+        if name.prefix.is_some() && !bindings.iter().any(|(p, _)| *p == name.prefix) {
+            bindings.push((name.prefix.clone(), name.ns.clone()));
+        }
+    }
+    bindings
+        .into_iter()
+        .filter(|(prefix, ns)| {
+            if prefix.as_deref() == Some("xml") {
+                return false;
+            }
+            let in_scope = node
+                .ancestors()
+                .filter_map(|a| a.value().as_element())
+                .find_map(|a| {
+                    if a.name.prefix == *prefix {
+                        return Some(a.name.ns.clone());
+                    }
+                    a.attrs
+                        .iter()
+                        .find(|(name, _)| name.prefix.is_some() && name.prefix == *prefix)
+                        .map(|(name, _)| name.ns.clone())
+                })
+                .unwrap_or(ns!());
+            in_scope != *ns
+        })
+        .map(|(prefix, ns)| {
+            (
+                match prefix {
+                    Some(prefix) => format!("xmlns:{prefix}"),
+                    None => String::from("xmlns"),
+                },
+                ns.to_string(),
+            )
+        })
+        .collect()
+}
+
+fn collapse_whitespace(text: &str, out: &mut String) {
+    let mut in_whitespace = out.ends_with(|c: char| c.is_ascii_whitespace());
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !in_whitespace {
+                out.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            out.push(c);
+            in_whitespace = false;
+        }
+    }
+}
+
+/// Escapes text or attribute values (like html5ever's serializer does).
+pub fn escape(text: &str, attribute: bool, syntax: Syntax) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{a0}' if syntax == Syntax::Html => out.push_str("&nbsp;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '<' if !attribute || syntax == Syntax::Xml => out.push_str("&lt;"),
+            '>' if !attribute => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The namespace bindings (`(xmlns:prefix, uri)`) used by an xml element and its ancestors
+/// (including the default namespace of unprefixed elements), outermost first.
+/// The parser drops the `xmlns` declarations, so unused declarations are not included.
+pub fn namespaces_used(node: NodeRef<'_, Node>) -> Vec<(String, String)> {
+    let mut elements: Vec<&Element> = node
+        .ancestors()
+        .filter_map(|a| a.value().as_element())
+        .collect();
+    elements.reverse();
+    elements.extend(node.value().as_element());
+
+    let mut bindings: Vec<(Option<Prefix>, Namespace)> = Vec::new();
+    let mut bind = |prefix: &Option<Prefix>, ns: &Namespace| {
+        if prefix.as_deref() == Some("xml") || (prefix.is_some() && *ns == ns!()) {
+            return;
+        }
+        match bindings.iter_mut().find(|(p, _)| p == prefix) {
+            Some(binding) => binding.1 = ns.clone(),
+            None => bindings.push((prefix.clone(), ns.clone())),
+        }
+    };
+    for element in elements {
+        bind(&element.name.prefix, &element.name.ns);
+        for (name, _) in element.attrs.iter() {
+            if name.prefix.is_some() {
+                bind(&name.prefix, &name.ns);
+            }
+        }
+    }
+    bindings
+        .into_iter()
+        // an unprefixed element without namespace only undeclares an outer default namespace
+        .filter(|(prefix, ns)| prefix.is_some() || *ns != ns!())
+        .map(|(prefix, ns)| {
+            (
+                match prefix {
+                    Some(prefix) => format!("xmlns:{prefix}"),
+                    None => String::from("xmlns"),
+                },
+                ns.to_string(),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,557 @@
+//! Content sniffing as described by the [WHATWG MIME Sniffing standard](https://mimesniff.spec.whatwg.org/).
+
+/// A byte pattern with a mask (`pattern[i] == input[i] & mask[i]`),
+/// leading bytes in `ignored` are skipped before matching.
+struct Pattern {
+    pattern: &'static [u8],
+    mask: &'static [u8],
+    ignored: &'static [u8],
+    mime: &'static str,
+}
+
+const fn pattern(pattern: &'static [u8], mask: &'static [u8], mime: &'static str) -> Pattern {
+    Pattern {
+        pattern,
+        mask,
+        ignored: b"",
+        mime,
+    }
+}
+
+/// <https://mimesniff.spec.whatwg.org/#whitespace-byte>
+const WHITESPACE: &[u8] = b"\t\n\x0C\r ";
+
+impl Pattern {
+    const fn skip_whitespace(mut self) -> Self {
+        self.ignored = WHITESPACE;
+        self
+    }
+
+    /// <https://mimesniff.spec.whatwg.org/#pattern-matching-algorithm>
+    fn matches(&self, input: &[u8]) -> bool {
+        let start = input
+            .iter()
+            .position(|b| !self.ignored.contains(b))
+            .unwrap_or(input.len());
+        let input = &input[start..];
+        input.len() >= self.pattern.len()
+            && self
+                .pattern
+                .iter()
+                .zip(self.mask)
+                .zip(input)
+                .all(|((p, m), i)| *p == i & m)
+    }
+}
+
+fn match_patterns(patterns: &[Pattern], input: &[u8]) -> Option<&'static str> {
+    patterns.iter().find(|p| p.matches(input)).map(|p| p.mime)
+}
+
+/// <https://mimesniff.spec.whatwg.org/#matching-an-image-type-pattern>
+const IMAGE_PATTERNS: [Pattern; 8] = [
+    pattern(b"\x00\x00\x01\x00", b"\xFF\xFF\xFF\xFF", "image/x-icon"),
+    pattern(b"\x00\x00\x02\x00", b"\xFF\xFF\xFF\xFF", "image/x-icon"),
+    pattern(b"BM", b"\xFF\xFF", "image/bmp"),
+    pattern(b"GIF87a", b"\xFF\xFF\xFF\xFF\xFF\xFF", "image/gif"),
+    pattern(b"GIF89a", b"\xFF\xFF\xFF\xFF\xFF\xFF", "image/gif"),
+    pattern(
+        b"RIFF\x00\x00\x00\x00WEBPVP",
+        b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF\xFF\xFF",
+        "image/webp",
+    ),
+    pattern(
+        b"\x89PNG\r\n\x1A\n",
+        b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF",
+        "image/png",
+    ),
+    pattern(b"\xFF\xD8\xFF", b"\xFF\xFF\xFF", "image/jpeg"),
+];
+
+/// <https://mimesniff.spec.whatwg.org/#matching-a-font-type-pattern>
+const FONT_PATTERNS: [Pattern; 6] = [
+    pattern(
+        b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00LP",
+        b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xFF\xFF",
+        "application/vnd.ms-fontobject",
+    ),
+    pattern(b"\x00\x01\x00\x00", b"\xFF\xFF\xFF\xFF", "font/ttf"),
+    pattern(b"OTTO", b"\xFF\xFF\xFF\xFF", "font/otf"),
+    pattern(b"ttcf", b"\xFF\xFF\xFF\xFF", "font/collection"),
+    pattern(b"wOFF", b"\xFF\xFF\xFF\xFF", "font/woff"),
+    pattern(b"wOF2", b"\xFF\xFF\xFF\xFF", "font/woff2"),
+];
+
+/// <https://mimesniff.spec.whatwg.org/#matching-an-archive-type-pattern>
+const ARCHIVE_PATTERNS: [Pattern; 3] = [
+    pattern(b"\x1F\x8B\x08", b"\xFF\xFF\xFF", "application/x-gzip"),
+    pattern(b"PK\x03\x04", b"\xFF\xFF\xFF\xFF", "application/zip"),
+    pattern(
+        b"Rar!\x1A\x07\x00",
+        b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF",
+        "application/x-rar-compressed",
+    ),
+];
+
+/// <https://mimesniff.spec.whatwg.org/#matching-an-audio-or-video-type-pattern>
+/// (without the mp4, webm and mp3 algorithms)
+const AUDIO_VIDEO_PATTERNS: [Pattern; 6] = [
+    pattern(
+        b"FORM\x00\x00\x00\x00AIFF",
+        b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF",
+        "audio/aiff",
+    ),
+    pattern(b"ID3", b"\xFF\xFF\xFF", "audio/mpeg"),
+    pattern(b"OggS\x00", b"\xFF\xFF\xFF\xFF\xFF", "application/ogg"),
+    pattern(
+        b"MThd\x00\x00\x00\x06",
+        b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF",
+        "audio/midi",
+    ),
+    pattern(
+        b"RIFF\x00\x00\x00\x00AVI ",
+        b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF",
+        "video/avi",
+    ),
+    pattern(
+        b"RIFF\x00\x00\x00\x00WAVE",
+        b"\xFF\xFF\xFF\xFF\x00\x00\x00\x00\xFF\xFF\xFF\xFF",
+        "audio/wave",
+    ),
+];
+
+/// The non-html patterns of the scriptable table
+/// (<https://mimesniff.spec.whatwg.org/#identifying-a-resource-with-an-unknown-mime-type>).
+const SCRIPTABLE_PATTERNS: [Pattern; 2] = [
+    pattern(b"<?xml", b"\xFF\xFF\xFF\xFF\xFF", "text/xml").skip_whitespace(),
+    pattern(b"%PDF-", b"\xFF\xFF\xFF\xFF\xFF", "application/pdf"),
+];
+
+/// Tags which identify html (followed by a space or `>`), matched case-insensitively.
+const HTML_TAGS: [&[u8]; 17] = [
+    b"<!DOCTYPE HTML",
+    b"<HTML",
+    b"<HEAD",
+    b"<SCRIPT",
+    b"<IFRAME",
+    b"<H1",
+    b"<DIV",
+    b"<FONT",
+    b"<TABLE",
+    b"<A",
+    b"<STYLE",
+    b"<TITLE",
+    b"<B",
+    b"<BODY",
+    b"<BR",
+    b"<P",
+    b"<!--",
+];
+
+const NON_SCRIPTABLE_PATTERNS: [Pattern; 4] = [
+    pattern(
+        b"%!PS-Adobe-",
+        b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF",
+        "application/postscript",
+    ),
+    // byte order marks
+    pattern(b"\xFE\xFF\x00\x00", b"\xFF\xFF\x00\x00", "text/plain"),
+    pattern(b"\xFF\xFE\x00\x00", b"\xFF\xFF\x00\x00", "text/plain"),
+    pattern(b"\xEF\xBB\xBF\x00", b"\xFF\xFF\xFF\x00", "text/plain"),
+];
+
+/// Only the first bytes of a resource are inspected.
+/// <https://mimesniff.spec.whatwg.org/#reading-the-resource-header>
+pub(crate) const RESOURCE_HEADER_LENGTH: usize = 1445;
+
+pub fn sniff_image(input: &[u8]) -> Option<&'static str> {
+    match_patterns(&IMAGE_PATTERNS, input)
+}
+
+pub fn sniff_font(input: &[u8]) -> Option<&'static str> {
+    match_patterns(&FONT_PATTERNS, input)
+}
+
+pub fn sniff_archive(input: &[u8]) -> Option<&'static str> {
+    match_patterns(&ARCHIVE_PATTERNS, input)
+}
+
+pub fn sniff_audio_video(input: &[u8]) -> Option<&'static str> {
+    match_patterns(&AUDIO_VIDEO_PATTERNS, input).or_else(|| {
+        if is_mp4(input) {
+            Some("video/mp4")
+        } else if is_webm(input) {
+            Some("video/webm")
+        } else if is_mp3_without_id3(input) {
+            Some("audio/mpeg")
+        } else {
+            None
+        }
+    })
+}
+
+fn is_html(input: &[u8]) -> bool {
+    let start = input
+        .iter()
+        .position(|b| !WHITESPACE.contains(b))
+        .unwrap_or(input.len());
+    let input = &input[start..];
+    HTML_TAGS.iter().any(|tag| {
+        input.len() > tag.len()
+            && input[..tag.len()].eq_ignore_ascii_case(tag)
+            && matches!(input[tag.len()], b' ' | b'>')
+    })
+}
+
+/// <https://mimesniff.spec.whatwg.org/#binary-data-byte>
+fn contains_binary_data(input: &[u8]) -> bool {
+    input
+        .iter()
+        .any(|b| matches!(b, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F))
+}
+
+/// <https://mimesniff.spec.whatwg.org/#rules-for-identifying-an-unknown-mime-type>
+pub fn sniff_unknown(input: &[u8], sniff_scriptable: bool) -> &'static str {
+    let input = &input[..input.len().min(RESOURCE_HEADER_LENGTH)];
+    if sniff_scriptable {
+        if is_html(input) {
+            return "text/html";
+        }
+        if let Some(mime) = match_patterns(&SCRIPTABLE_PATTERNS, input) {
+            return mime;
+        }
+    }
+    match_patterns(&NON_SCRIPTABLE_PATTERNS, input)
+        .or_else(|| sniff_image(input))
+        .or_else(|| sniff_audio_video(input))
+        .or_else(|| sniff_archive(input))
+        .unwrap_or(if contains_binary_data(input) {
+            "application/octet-stream"
+        } else {
+            "text/plain"
+        })
+}
+
+/// <https://mimesniff.spec.whatwg.org/#rules-for-text-or-binary>
+fn sniff_text_or_binary(input: &[u8]) -> &'static str {
+    let input = &input[..input.len().min(RESOURCE_HEADER_LENGTH)];
+    if input.starts_with(b"\xFE\xFF")
+        || input.starts_with(b"\xFF\xFE")
+        || input.starts_with(b"\xEF\xBB\xBF")
+        || !contains_binary_data(input)
+    {
+        return "text/plain";
+    }
+    sniff_unknown(input, false)
+}
+
+/// <https://mimesniff.spec.whatwg.org/#rules-for-distinguishing-if-a-resource-is-a-feed-or-html>
+fn sniff_feed_or_html(input: &[u8]) -> &'static str {
+    let input = &input[..input.len().min(RESOURCE_HEADER_LENGTH)];
+    let mut rest = input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(input);
+    loop {
+        // skip whitespace and find the next `<`
+        let Some(start) = rest.iter().position(|b| !WHITESPACE.contains(b)) else {
+            return "text/html";
+        };
+        rest = &rest[start..];
+        if !rest.starts_with(b"<") {
+            return "text/html";
+        }
+        rest = &rest[1..];
+        let skip_to = |rest: &[u8], end: &[u8]| -> Option<usize> {
+            rest.windows(end.len())
+                .position(|w| w == end)
+                .map(|i| i + end.len())
+        };
+        if rest.starts_with(b"!--") {
+            let Some(end) = skip_to(rest, b"-->") else {
+                return "text/html";
+            };
+            rest = &rest[end..];
+        } else if rest.starts_with(b"!") || rest.starts_with(b"?") {
+            let end_marker: &[u8] = if rest.starts_with(b"?") { b"?>" } else { b">" };
+            let Some(end) = skip_to(rest, end_marker) else {
+                return "text/html";
+            };
+            rest = &rest[end..];
+        } else if rest.starts_with(b"rss") {
+            return "application/rss+xml";
+        } else if rest.starts_with(b"feed") {
+            return "application/atom+xml";
+        } else if rest.starts_with(b"rdf:RDF") {
+            let contains = |needle: &[u8]| rest.windows(needle.len()).any(|w| w == needle);
+            if contains(b"http://purl.org/rss/1.0/")
+                && contains(b"http://www.w3.org/1999/02/22-rdf-syntax-ns#")
+            {
+                return "application/rss+xml";
+            }
+            return "text/html";
+        } else {
+            return "text/html";
+        }
+    }
+}
+
+/// <https://mimesniff.spec.whatwg.org/#signature-for-mp4>
+fn is_mp4(input: &[u8]) -> bool {
+    if input.len() < 12 {
+        return false;
+    }
+    let box_size = u32::from_be_bytes([input[0], input[1], input[2], input[3]]) as usize;
+    if input.len() < box_size || !box_size.is_multiple_of(4) || &input[4..8] != b"ftyp" {
+        return false;
+    }
+    if &input[8..11] == b"mp4" {
+        return true;
+    }
+    (16..box_size)
+        .step_by(4)
+        .any(|i| input.get(i..i + 3) == Some(b"mp4"))
+}
+
+/// <https://mimesniff.spec.whatwg.org/#signature-for-webm>
+fn is_webm(input: &[u8]) -> bool {
+    if !input.starts_with(b"\x1A\x45\xDF\xA3") {
+        return false;
+    }
+    let mut i = 4;
+    while i + 1 < input.len() && i < 38 {
+        if input[i] == 0x42 && input[i + 1] == 0x82 {
+            i += 2;
+            if i >= input.len() {
+                break;
+            }
+            i += vint_size(input, i);
+            if i + 4 > input.len() {
+                break;
+            }
+            // "matching a padded sequence"
+            let start = input[i..]
+                .iter()
+                .position(|b| *b != 0)
+                .map_or(input.len(), |p| i + p);
+            if input.get(start..start + 4) == Some(b"webm") {
+                return true;
+            }
+        }
+        i += 1;
+    }
+    false
+}
+
+/// <https://mimesniff.spec.whatwg.org/#parse-a-vint>
+fn vint_size(input: &[u8], i: usize) -> usize {
+    let mut mask = 0x80;
+    let mut size = 1;
+    while size < 8 && input[i] & mask == 0 {
+        mask >>= 1;
+        size += 1;
+    }
+    size
+}
+
+/// <https://mimesniff.spec.whatwg.org/#signature-for-mp3-without-id3>
+fn is_mp3_without_id3(input: &[u8]) -> bool {
+    const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+    const MP3_RATES: [u32; 15] = [
+        0, 32000, 40000, 48000, 56000, 64000, 80000, 96000, 112000, 128000, 160000, 192000, 224000,
+        256000, 320000,
+    ];
+    const MP25_RATES: [u32; 15] = [
+        0, 8000, 16000, 24000, 32000, 40000, 48000, 56000, 64000, 80000, 96000, 112000, 128000,
+        144000, 160000,
+    ];
+
+    let is_header = |i: usize| -> bool {
+        let Some(h) = input.get(i..i + 4) else {
+            return false;
+        };
+        let layer = (h[1] & 0x06) >> 1;
+        let bit_rate = (h[2] & 0xF0) >> 4;
+        let sample_rate = (h[2] & 0x0C) >> 2;
+        h[0] == 0xFF
+            && h[1] & 0xE0 == 0xE0
+            && layer != 0
+            && bit_rate != 15
+            && sample_rate != 3
+            && !(4 - layer == 3 && bit_rate == 0)
+    };
+    if !is_header(0) {
+        return false;
+    }
+    let version = (input[1] & 0x18) >> 3;
+    let bit_rate_index = ((input[2] & 0xF0) >> 4) as usize;
+    let bit_rate = if version & 0x01 != 0 {
+        MP25_RATES[bit_rate_index]
+    } else {
+        MP3_RATES[bit_rate_index]
+    };
+    let sample_rate = SAMPLE_RATES[((input[2] & 0x0C) >> 2) as usize];
+    let padding = u32::from((input[2] & 0x02) >> 1);
+    let scale = if version == 1 { 72 } else { 144 };
+    let frame_size = (bit_rate * scale / sample_rate + padding) as usize;
+    if frame_size < 4 || frame_size > input.len() {
+        return false;
+    }
+    is_header(frame_size)
+}
+
+/// Where a resource is used (<https://mimesniff.spec.whatwg.org/#context-specific-sniffing>).
+#[derive(Copy, Clone, PartialEq)]
+pub enum SniffContext {
+    Browsing,
+    Image,
+    AudioVideo,
+    Font,
+    Plugin,
+    Style,
+    Script,
+    TextTrack,
+    CacheManifest,
+}
+
+impl SniffContext {
+    pub const NAMES: [&str; 9] = [
+        "browsing",
+        "image",
+        "audio-video",
+        "font",
+        "plugin",
+        "style",
+        "script",
+        "text-track",
+        "cache-manifest",
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "browsing" => Self::Browsing,
+            "image" => Self::Image,
+            "audio-video" => Self::AudioVideo,
+            "font" => Self::Font,
+            "plugin" => Self::Plugin,
+            "style" => Self::Style,
+            "script" => Self::Script,
+            "text-track" => Self::TextTrack,
+            "cache-manifest" => Self::CacheManifest,
+            _ => return None,
+        })
+    }
+}
+
+/// The `Content-Type` of a resource.
+pub struct SuppliedType<'a> {
+    /// the header value as it was sent
+    pub raw: &'a str,
+    /// lowercase `type/subtype`
+    pub essence: String,
+}
+
+impl<'a> SuppliedType<'a> {
+    pub fn new(raw: &'a str) -> Self {
+        let essence = raw
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        Self { raw, essence }
+    }
+
+    fn is_unknown(&self) -> bool {
+        matches!(
+            self.essence.as_str(),
+            "" | "unknown/unknown" | "application/unknown" | "*/*"
+        )
+    }
+
+    /// <https://mimesniff.spec.whatwg.org/#xml-mime-type>
+    fn is_xml(&self) -> bool {
+        self.essence.ends_with("+xml")
+            || self.essence == "text/xml"
+            || self.essence == "application/xml"
+    }
+}
+
+/// Determines the computed mime type of a resource
+/// (<https://mimesniff.spec.whatwg.org/#determining-the-computed-mime-type-of-a-resource>).
+/// Returns the supplied type (as it is) or a sniffed `type/subtype`.
+pub fn sniff<'a>(
+    input: &[u8],
+    supplied: Option<&SuppliedType<'a>>,
+    no_sniff: bool,
+    context: SniffContext,
+) -> std::borrow::Cow<'a, str> {
+    use std::borrow::Cow;
+
+    let supplied = supplied.filter(|s| !s.is_unknown());
+    let supplied_or = |default: &'static str| match supplied {
+        Some(supplied) => Cow::Borrowed(supplied.raw),
+        None => Cow::Borrowed(default),
+    };
+    match context {
+        SniffContext::Browsing => {}
+        SniffContext::Image | SniffContext::AudioVideo | SniffContext::Font => {
+            if let Some(supplied) = supplied
+                && (supplied.is_xml() || no_sniff)
+            {
+                return Cow::Borrowed(supplied.raw);
+            }
+            let sniffed = match context {
+                SniffContext::Image => sniff_image(input),
+                SniffContext::AudioVideo => sniff_audio_video(input),
+                _ => sniff_font(input),
+            };
+            return match sniffed {
+                Some(sniffed) => Cow::Borrowed(sniffed),
+                None => supplied_or("application/octet-stream"),
+            };
+        }
+        SniffContext::Plugin => return supplied_or("application/octet-stream"),
+        SniffContext::Style => return supplied_or("text/css"),
+        SniffContext::Script => return supplied_or("text/javascript"),
+        SniffContext::TextTrack => return Cow::Borrowed("text/vtt"),
+        SniffContext::CacheManifest => return Cow::Borrowed("text/cache-manifest"),
+    }
+
+    let Some(supplied) = supplied else {
+        return Cow::Borrowed(sniff_unknown(input, !no_sniff));
+    };
+    if no_sniff {
+        return Cow::Borrowed(supplied.raw);
+    }
+    // servers (such as old versions of apache) send these for unknown files
+    if matches!(
+        supplied.raw,
+        "text/plain"
+            | "text/plain; charset=ISO-8859-1"
+            | "text/plain; charset=iso-8859-1"
+            | "text/plain; charset=UTF-8"
+    ) {
+        return Cow::Borrowed(sniff_text_or_binary(input));
+    }
+    if supplied.is_xml() {
+        return Cow::Borrowed(supplied.raw);
+    }
+    if supplied.essence == "text/html" {
+        return match sniff_feed_or_html(input) {
+            "text/html" => Cow::Borrowed(supplied.raw),
+            feed => Cow::Borrowed(feed),
+        };
+    }
+    let sniffed = if supplied.essence.starts_with("image/") {
+        sniff_image(input)
+    } else if supplied.essence.starts_with("audio/")
+        || supplied.essence.starts_with("video/")
+        || supplied.essence == "application/ogg"
+    {
+        sniff_audio_video(input)
+    } else {
+        None
+    };
+    match sniffed {
+        Some(sniffed) => Cow::Borrowed(sniffed),
+        None => Cow::Borrowed(supplied.raw),
+    }
+}
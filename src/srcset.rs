@@ -0,0 +1,64 @@
+//! `srcset=""` attributes (`a.png 1x, b.png 2x`).
+
+/// An image candidate: the url and its (unparsed) descriptors such as `2x` or `100w`.
+pub struct Candidate {
+    pub url: String,
+    pub descriptors: String,
+}
+
+/// Splits a srcset into candidates. Unlike `split(',')` this keeps commas inside urls
+/// (such as `data:` urls), as described in the html standard.
+pub fn parse_srcset(srcset: &str) -> Vec<Candidate> {
+    let is_space = |c: char| c.is_ascii_whitespace();
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| is_space(c) || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+        let url_end = rest.find(is_space).unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        rest = &rest[url_end..];
+        // a trailing comma ends the candidate, there are no descriptors
+        let trimmed_url = url.trim_end_matches(',');
+        let descriptors = if trimmed_url.len() != url.len() {
+            ""
+        } else {
+            let mut depth = 0usize;
+            let end = rest
+                .char_indices()
+                .find(|(_, c)| {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth = depth.saturating_sub(1),
+                        ',' if depth == 0 => return true,
+                        _ => {}
+                    }
+                    false
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            let descriptors = rest[..end].trim();
+            rest = &rest[end..];
+            descriptors
+        };
+        candidates.push(Candidate {
+            url: trimmed_url.to_owned(),
+            descriptors: descriptors.to_owned(),
+        });
+    }
+}
+
+pub fn serialize_srcset(candidates: &[Candidate]) -> String {
+    candidates
+        .iter()
+        .map(|c| {
+            if c.descriptors.is_empty() {
+                c.url.clone()
+            } else {
+                format!("{} {}", c.url, c.descriptors)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}